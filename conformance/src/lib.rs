@@ -0,0 +1,266 @@
+//! Declarative Intcode conformance suite.
+//!
+//! A suite is a text file of cases. Each case starts with a `case <name>`
+//! line and is followed by `key: value` lines; blank lines and lines
+//! starting with `#` are ignored.
+//!
+//! ```text
+//! case echo
+//! level: day05
+//! program: 3,0,4,0,99
+//! input: 1337
+//! output: 1337
+//! memory 0: 1337,0,4,0,99
+//! ```
+//!
+//! - `level` (required) is the instruction set the case needs: `day02`,
+//!   `day05` or `day09`. Interpreters below that level skip the case.
+//! - `program` (required) is the comma-separated image.
+//! - `input` is fed in order; it defaults to no input.
+//! - `output`, if given, must match the outputs exactly.
+//! - `memory <addr>` checks the final memory starting at `addr` and may be
+//!   repeated.
+//! - `outcome` is one of `halted` (the default), `awaiting-input`,
+//!   `step-limit` or `crashed`.
+
+use intcode::Level;
+use std::fmt;
+use targets::{Outcome, Target};
+
+/// Every case in the suite terminates well before this.
+pub const STEP_LIMIT: usize = 100_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub name: String,
+    pub line: usize,
+    pub level: Level,
+    pub program: Vec<i64>,
+    pub inputs: Vec<i64>,
+    pub outputs: Option<Vec<i64>>,
+    pub memory: Vec<(usize, Vec<i64>)>,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn error<T>(line: usize, message: String) -> Result<T, ParseError> {
+    Err(ParseError { line, message })
+}
+
+fn parse_values(line: usize, text: &str) -> Result<Vec<i64>, ParseError> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    text.split(',')
+        .map(|token| match token.trim().parse() {
+            Ok(value) => Ok(value),
+            Err(_) => error(line, format!("bad value {:?}", token.trim())),
+        })
+        .collect()
+}
+
+fn parse_level(line: usize, text: &str) -> Result<Level, ParseError> {
+    match text {
+        "day02" => Ok(Level::Day02),
+        "day05" => Ok(Level::Day05),
+        "day09" => Ok(Level::Day09),
+        _ => error(line, format!("unknown level {:?}", text)),
+    }
+}
+
+fn parse_outcome(line: usize, text: &str) -> Result<Outcome, ParseError> {
+    match text {
+        "halted" => Ok(Outcome::Halted),
+        "awaiting-input" => Ok(Outcome::AwaitingInput),
+        "step-limit" => Ok(Outcome::StepLimit),
+        "crashed" => Ok(Outcome::Crashed(String::new())),
+        _ => error(line, format!("unknown outcome {:?}", text)),
+    }
+}
+
+/// Fields of the case being parsed; `level` and `program` are checked once
+/// the case is complete.
+struct Partial {
+    name: String,
+    line: usize,
+    level: Option<Level>,
+    program: Option<Vec<i64>>,
+    inputs: Vec<i64>,
+    outputs: Option<Vec<i64>>,
+    memory: Vec<(usize, Vec<i64>)>,
+    outcome: Outcome,
+}
+
+impl Partial {
+    fn finish(self) -> Result<Case, ParseError> {
+        let level = match self.level {
+            Some(level) => level,
+            None => return error(self.line, format!("case {:?} has no level", self.name)),
+        };
+        let program = match self.program {
+            Some(program) => program,
+            None => return error(self.line, format!("case {:?} has no program", self.name)),
+        };
+        Ok(Case {
+            name: self.name,
+            line: self.line,
+            level,
+            program,
+            inputs: self.inputs,
+            outputs: self.outputs,
+            memory: self.memory,
+            outcome: self.outcome,
+        })
+    }
+}
+
+pub fn parse(text: &str) -> Result<Vec<Case>, ParseError> {
+    let mut cases = Vec::new();
+    let mut current: Option<Partial> = None;
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let raw = raw.trim();
+        if raw.is_empty() || raw.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = raw.strip_prefix("case ") {
+            if let Some(partial) = current.take() {
+                cases.push(partial.finish()?);
+            }
+            current = Some(Partial {
+                name: name.trim().to_string(),
+                line,
+                level: None,
+                program: None,
+                inputs: Vec::new(),
+                outputs: None,
+                memory: Vec::new(),
+                outcome: Outcome::Halted,
+            });
+            continue;
+        }
+        let partial = match current.as_mut() {
+            Some(partial) => partial,
+            None => return error(line, String::from("expected `case <name>`")),
+        };
+        let (key, value) = match raw.find(':') {
+            Some(at) => (raw[..at].trim(), raw[at + 1..].trim()),
+            None => return error(line, format!("expected `key: value`, got {:?}", raw)),
+        };
+        match key {
+            "level" => partial.level = Some(parse_level(line, value)?),
+            "program" => partial.program = Some(parse_values(line, value)?),
+            "input" => partial.inputs = parse_values(line, value)?,
+            "output" => partial.outputs = Some(parse_values(line, value)?),
+            "outcome" => partial.outcome = parse_outcome(line, value)?,
+            _ => match key.strip_prefix("memory ") {
+                Some(addr) => match addr.trim().parse() {
+                    Ok(addr) => partial.memory.push((addr, parse_values(line, value)?)),
+                    Err(_) => return error(line, format!("bad address {:?}", addr)),
+                },
+                None => return error(line, format!("unknown key {:?}", key)),
+            },
+        }
+    }
+    if let Some(partial) = current {
+        cases.push(partial.finish()?);
+    }
+    Ok(cases)
+}
+
+/// Run one case and describe the first expectation it misses.
+pub fn check(target: &dyn Target, case: &Case) -> Result<(), String> {
+    let run = target.execute(&case.program, &case.inputs, STEP_LIMIT);
+    let outcome_matches = match (&case.outcome, &run.outcome) {
+        (Outcome::Crashed(_), Outcome::Crashed(_)) => true,
+        (expected, actual) => expected == actual,
+    };
+    if !outcome_matches {
+        return Err(format!(
+            "expected {:?}, got {:?}",
+            case.outcome, run.outcome
+        ));
+    }
+    if let Some(outputs) = &case.outputs {
+        if *outputs != run.outputs {
+            return Err(format!(
+                "expected outputs {:?}, got {:?}",
+                outputs, run.outputs
+            ));
+        }
+    }
+    for (addr, expected) in case.memory.iter() {
+        let actual = run.memory.get(*addr..*addr + expected.len());
+        if actual != Some(&expected[..]) {
+            return Err(format!(
+                "expected memory {:?} at {}, got {:?}",
+                expected, addr, actual
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub target: &'static str,
+    pub case: String,
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:?} (line {}): {}",
+            self.target, self.case, self.line, self.reason
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub passed: usize,
+    pub skipped: usize,
+    pub failures: Vec<Failure>,
+}
+
+/// Run every case on every target that supports its level.
+pub fn run(targets: &[Box<dyn Target>], cases: &[Case]) -> Report {
+    let mut report = Report::default();
+    for target in targets.iter() {
+        for case in cases.iter() {
+            if target.level() < case.level {
+                report.skipped += 1;
+                continue;
+            }
+            match check(target.as_ref(), case) {
+                Ok(()) => report.passed += 1,
+                Err(reason) => report.failures.push(Failure {
+                    target: target.name(),
+                    case: case.name.clone(),
+                    line: case.line,
+                    reason,
+                }),
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,30 @@
+use std::env;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::process;
+use targets::all_targets;
+
+fn main() -> Result<(), Error> {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("suite.txt"));
+    let text = fs::read_to_string(&path)?;
+    let cases = conformance::parse(&text)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", path, e)))?;
+
+    let report = conformance::run(&all_targets(), &cases);
+    for failure in report.failures.iter() {
+        println!("FAIL {}", failure);
+    }
+    println!(
+        "{} cases: {} passed, {} failed, {} skipped",
+        cases.len(),
+        report.passed,
+        report.failures.len(),
+        report.skipped
+    );
+    if !report.failures.is_empty() {
+        process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use super::*;
+use targets::{all_targets, Reference, Run};
+
+const SUITE: &str = include_str!("../suite.txt");
+
+#[test]
+fn test_parse() {
+    let cases = parse(
+        "# comment\n\
+         case echo\n\
+         level: day05\n\
+         program: 3,0,4,0,99\n\
+         input: 1337\n\
+         output: 1337\n\
+         memory 0: 1337, 0\n\
+         memory 4: 99\n\
+         \n\
+         case block\n\
+         level: day09\n\
+         program: 3,0,99\n\
+         outcome: awaiting-input\n",
+    )
+    .unwrap();
+    assert_eq!(cases.len(), 2);
+    assert_eq!(cases[0].name, "echo");
+    assert_eq!(cases[0].line, 2);
+    assert_eq!(cases[0].level, Level::Day05);
+    assert_eq!(cases[0].program, vec![3, 0, 4, 0, 99]);
+    assert_eq!(cases[0].inputs, vec![1337]);
+    assert_eq!(cases[0].outputs, Some(vec![1337]));
+    assert_eq!(cases[0].memory, vec![(0, vec![1337, 0]), (4, vec![99])]);
+    assert_eq!(cases[0].outcome, Outcome::Halted);
+    assert_eq!(cases[1].outputs, None);
+    assert_eq!(cases[1].outcome, Outcome::AwaitingInput);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(parse("level: day02").unwrap_err().line, 1);
+    assert_eq!(
+        parse("case a\nlevel: day02\nprogram: 1,x").unwrap_err(),
+        ParseError {
+            line: 3,
+            message: String::from("bad value \"x\""),
+        }
+    );
+    assert_eq!(parse("case a\nprogram: 99").unwrap_err().line, 1);
+    assert_eq!(parse("case a\nlevel: day03").unwrap_err().line, 2);
+    assert_eq!(parse("case a\ncolour: red").unwrap_err().line, 2);
+}
+
+/// Reports whatever it is told to, regardless of the program.
+struct Canned(Run);
+
+impl Target for Canned {
+    fn name(&self) -> &'static str {
+        "canned"
+    }
+
+    fn level(&self) -> Level {
+        Level::Day09
+    }
+
+    fn execute(&self, _program: &[i64], _inputs: &[i64], _limit: usize) -> Run {
+        self.0.clone()
+    }
+}
+
+#[test]
+fn test_check() {
+    let case =
+        &parse("case a\nlevel: day05\nprogram: 3,0,4,0,99\ninput: 5\noutput: 5\nmemory 0: 5")
+            .unwrap()[0];
+    assert_eq!(check(&Reference, case), Ok(()));
+
+    let wrong_output = Canned(Run {
+        outcome: Outcome::Halted,
+        memory: vec![5, 0, 4, 0, 99],
+        outputs: vec![6],
+    });
+    assert!(check(&wrong_output, case).is_err());
+
+    let short_memory = Canned(Run {
+        outcome: Outcome::Halted,
+        memory: vec![],
+        outputs: vec![5],
+    });
+    assert!(check(&short_memory, case).is_err());
+
+    let crashed = Canned(Run {
+        outcome: Outcome::Crashed(String::from("boom")),
+        memory: vec![5, 0, 4, 0, 99],
+        outputs: vec![5],
+    });
+    assert!(check(&crashed, case).is_err());
+}
+
+#[test]
+fn test_suite() {
+    let cases = parse(SUITE).unwrap();
+    let report = run(&all_targets(), &cases);
+    for failure in report.failures.iter() {
+        println!("{}", failure);
+    }
+    assert!(report.failures.is_empty());
+    assert!(report.passed > cases.len());
+}
@@ -1,77 +1,16 @@
-use std::io::{self, BufRead, Error};
+use intcode::{read_program, Computer, Level, Memory};
+use std::error::Error;
+use std::io;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Instruction {
-    ADD = 1,
-    MUL = 2,
-    EXIT = 99,
+fn run(program: Memory) -> Memory {
+    Computer::with_level(program, Level::Day02)
+        .run()
+        .memory()
+        .to_vec()
 }
 
-impl From<isize> for Instruction {
-    fn from(number: isize) -> Self {
-        match number {
-            1 => Instruction::ADD,
-            2 => Instruction::MUL,
-            99 => Instruction::EXIT,
-            _ => panic!(format!("Bad instruction: {}", number)),
-        }
-    }
-}
-
-#[derive(Debug, Copy, Clone)]
-struct Opcode {
-    instr: Instruction,
-    input1: usize,
-    input2: usize,
-    output_address: usize,
-}
-
-impl Opcode {
-    fn new(instr: isize, input1: isize, input2: isize, output_address: isize) -> Self {
-        Opcode {
-            instr: Instruction::from(instr),
-            input1: input1 as usize,
-            input2: input2 as usize,
-            output_address: output_address as usize,
-        }
-    }
-}
-
-fn run(program: Vec<isize>) -> Vec<isize> {
-    let mut result = program.clone();
-    let mut eip = 0;
-    loop {
-        if Instruction::from(result[eip]) == Instruction::EXIT {
-            break result;
-        }
-        let opcode = Opcode::new(
-            result[eip],
-            result[eip + 1],
-            result[eip + 2],
-            result[eip + 3],
-        );
-        match opcode.instr {
-            Instruction::ADD => {
-                result[opcode.output_address] = result[opcode.input1] + result[opcode.input2]
-            }
-            Instruction::MUL => {
-                result[opcode.output_address] = result[opcode.input1] * result[opcode.input2]
-            }
-            Instruction::EXIT => break result,
-        }
-        eip += 4;
-    }
-}
-
-fn main() -> Result<(), Error> {
-    let stdin = io::stdin();
-    // initialize with invalid input
-    let mut codes: Vec<isize> = vec![-1, -1, -1, -1];
-    for program in stdin.lock().lines() {
-        let program = String::from(program?);
-        let tokens: Vec<&str> = program.split(",").collect();
-        codes = tokens.iter().map(|x| x.parse().unwrap()).collect();
-    }
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut codes = read_program(io::stdin().lock())?;
     codes[1] = 12;
     codes[2] = 2;
 
@@ -79,14 +18,15 @@ fn main() -> Result<(), Error> {
     let result = run(codes.clone());
     println!("day 1: {:?}", result);
 
-    // day 2
+    // day 2: clones share memory pages until noun and verb are patched in
+    let base = Computer::with_level(codes, Level::Day02);
     for noun in 0..99 {
         for verb in 0..99 {
-            let mut candidate = codes.clone();
-            candidate[1] = noun;
-            candidate[2] = verb;
-            let result = run(candidate);
-            if result[0] == 19690720 {
+            let mut candidate = base.clone();
+            candidate.set(1, noun);
+            candidate.set(2, verb);
+            candidate.run_to_halt();
+            if candidate.memory()[0] == 19690720 {
                 let answer = 100 * noun + verb;
                 println!("day 2: answer={:} [noun={:} verb={:}]", answer, noun, verb);
                 return Ok(());
@@ -99,12 +39,13 @@ fn main() -> Result<(), Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use intcode::Opcode;
 
     #[test]
     fn test_instruction() {
-        assert_eq!(Instruction::from(1), Instruction::ADD);
-        assert_eq!(Instruction::from(2), Instruction::MUL);
-        assert_eq!(Instruction::from(99), Instruction::EXIT);
+        assert_eq!(Opcode::new(1), Opcode::Add);
+        assert_eq!(Opcode::new(2), Opcode::Multiply);
+        assert_eq!(Opcode::new(99), Opcode::Exit);
     }
 
     #[test]
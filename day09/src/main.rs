@@ -1,35 +1,22 @@
 extern crate env_logger;
 
-use intcode::{Computer, Memory};
+use intcode::{read_program, Computer};
+use std::error::Error;
 use std::io;
-use std::io::{BufRead, Error};
 
-fn read_program() -> Result<Computer, Error> {
-    let stdin = io::stdin();
-    let mut codes: Memory = Vec::new();
-    for line in stdin.lock().lines() {
-        let line = line?;
-        let tokens: Vec<&str> = line.split(',').collect();
-        for token in tokens.into_iter() {
-            codes.push(token.parse().unwrap());
-        }
-    }
-    Ok(Computer::new(codes))
-}
-
-fn main() -> Result<(), Error> {
+fn main() -> Result<(), Box<dyn Error>> {
     env_logger::init();
-    let program = read_program()?;
+    let program = Computer::new(read_program(io::stdin().lock())?);
 
     let mut computer = program.clone();
-    computer.inputs.push(1);
-    let result = computer.run();
-    println!("part 1: {:?}", result.outputs.get(0));
+    computer.queue_inputs(Some(1));
+    computer.run_to_halt();
+    println!("part 1: {:?}", computer.outputs.first());
 
     let mut computer = program.clone();
-    computer.inputs.push(2);
-    let result = computer.run();
-    println!("part 2: {:?}", result.outputs.get(0));
+    computer.queue_inputs(Some(2));
+    computer.run_to_halt();
+    println!("part 2: {:?}", computer.outputs.first());
 
     Ok(())
 }
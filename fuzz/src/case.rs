@@ -0,0 +1,358 @@
+use intcode::{Level, Rng};
+use std::cmp;
+use std::fmt;
+use Level::*;
+use Op::*;
+
+const MAX_OPS: usize = 24;
+const MAX_VALUE: i64 = 50;
+
+/// Data cells live after the exit instruction: seeds are initialised from
+/// the case and never written, scratch cells start at zero.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Region {
+    Seed,
+    Scratch,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cell {
+    pub region: Region,
+    pub index: usize,
+    pub relative: bool,
+}
+
+impl Cell {
+    fn level(&self) -> Level {
+        if self.relative {
+            Day09
+        } else {
+            Day02
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Src {
+    Immediate(i64),
+    Cell(Cell),
+}
+
+impl Src {
+    fn level(&self) -> Level {
+        match self {
+            Src::Immediate(_) => Day05,
+            Src::Cell(cell) => cell.level(),
+        }
+    }
+}
+
+/// A symbolic instruction. Jump targets are indices into `Case::ops`, where
+/// `ops.len()` means the trailing exit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Op {
+    Add(Src, Src, Cell),
+    Multiply(Src, Src, Cell),
+    Input(Cell),
+    Output(Src),
+    JumpTrue(Src, usize),
+    JumpFalse(Src, usize),
+    LessThan(Src, Src, Cell),
+    Equals(Src, Src, Cell),
+    SetRelativeBase(i64),
+}
+
+impl Op {
+    fn code(&self) -> i64 {
+        match self {
+            Add(..) => 1,
+            Multiply(..) => 2,
+            Input(_) => 3,
+            Output(_) => 4,
+            JumpTrue(..) => 5,
+            JumpFalse(..) => 6,
+            LessThan(..) => 7,
+            Equals(..) => 8,
+            SetRelativeBase(_) => 9,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Add(..) | Multiply(..) | LessThan(..) | Equals(..) => 4,
+            JumpTrue(..) | JumpFalse(..) => 3,
+            Input(_) | Output(_) | SetRelativeBase(_) => 2,
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        match self {
+            Add(a, b, c) | Multiply(a, b, c) => cmp::max(cmp::max(a.level(), b.level()), c.level()),
+            Input(c) => cmp::max(Day05, c.level()),
+            Output(a) | JumpTrue(a, _) | JumpFalse(a, _) => cmp::max(Day05, a.level()),
+            LessThan(a, b, c) | Equals(a, b, c) => {
+                cmp::max(Day05, cmp::max(cmp::max(a.level(), b.level()), c.level()))
+            }
+            SetRelativeBase(_) => Day09,
+        }
+    }
+
+    pub fn sources_mut(&mut self) -> Vec<&mut Src> {
+        match self {
+            Add(a, b, _) | Multiply(a, b, _) | LessThan(a, b, _) | Equals(a, b, _) => vec![a, b],
+            Output(a) | JumpTrue(a, _) | JumpFalse(a, _) => vec![a],
+            Input(_) | SetRelativeBase(_) => vec![],
+        }
+    }
+
+    pub fn sources(&self) -> Vec<Src> {
+        match *self {
+            Add(a, b, _) | Multiply(a, b, _) | LessThan(a, b, _) | Equals(a, b, _) => vec![a, b],
+            Output(a) | JumpTrue(a, _) | JumpFalse(a, _) => vec![a],
+            Input(_) | SetRelativeBase(_) => vec![],
+        }
+    }
+
+    pub fn cells(&self) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = self
+            .sources()
+            .into_iter()
+            .filter_map(|s| match s {
+                Src::Cell(cell) => Some(cell),
+                Src::Immediate(_) => None,
+            })
+            .collect();
+        if let Some(cell) = self.target() {
+            cells.push(cell);
+        }
+        cells
+    }
+
+    pub fn cells_mut(&mut self) -> Vec<&mut Cell> {
+        match self {
+            Add(a, b, c) | Multiply(a, b, c) | LessThan(a, b, c) | Equals(a, b, c) => {
+                let mut cells = vec![c];
+                for s in [a, b] {
+                    if let Src::Cell(cell) = s {
+                        cells.push(cell);
+                    }
+                }
+                cells
+            }
+            Input(c) => vec![c],
+            Output(Src::Cell(cell))
+            | JumpTrue(Src::Cell(cell), _)
+            | JumpFalse(Src::Cell(cell), _) => {
+                vec![cell]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn target(&self) -> Option<Cell> {
+        match self {
+            Add(_, _, c) | Multiply(_, _, c) | LessThan(_, _, c) | Equals(_, _, c) | Input(c) => {
+                Some(*c)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn label_mut(&mut self) -> Option<&mut usize> {
+        match self {
+            JumpTrue(_, label) | JumpFalse(_, label) => Some(label),
+            _ => None,
+        }
+    }
+}
+
+/// A generated program together with the inputs it will be fed.
+///
+/// Programs are valid for every interpreter at or above their level: writes
+/// only go to scratch cells, jumps only go forward so every run terminates,
+/// there is one input per `Input` and values stay well inside `i32`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub ops: Vec<Op>,
+    pub seeds: Vec<i64>,
+    pub inputs: Vec<i64>,
+}
+
+impl Case {
+    pub fn generate(rng: &mut Rng, level: Level) -> Self {
+        let seeds: Vec<i64> = (0..1 + rng.below(5))
+            .map(|_| rng.range(-MAX_VALUE, MAX_VALUE + 1))
+            .collect();
+        let scratch = 1 + rng.below(5);
+        let mut generator = Generator {
+            rng,
+            level,
+            seeds: seeds.len(),
+            scratch,
+        };
+        let mut ops = Vec::new();
+        let mut inputs = Vec::new();
+        if level == Day09 {
+            for _ in 0..generator.rng.below(3) {
+                ops.push(SetRelativeBase(generator.rng.range(-20, 40)));
+            }
+        }
+        let n = ops.len() + 1 + generator.rng.below(MAX_OPS);
+        while ops.len() < n {
+            let at = ops.len();
+            let op = generator.op(at, n);
+            if let Input(_) = op {
+                inputs.push(generator.rng.range(-100, 101));
+            }
+            ops.push(op);
+        }
+        Case { ops, seeds, inputs }
+    }
+
+    pub fn level(&self) -> Level {
+        self.ops.iter().map(|op| op.level()).max().unwrap_or(Day02)
+    }
+
+    pub fn input_ops(&self) -> usize {
+        self.ops.iter().filter(|op| matches!(op, Input(_))).count()
+    }
+
+    pub fn scratch_len(&self) -> usize {
+        self.ops
+            .iter()
+            .flat_map(|op| op.cells())
+            .filter(|cell| cell.region == Region::Scratch)
+            .map(|cell| cell.index + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Lay the case out as `ops, 99, seeds, scratch`.
+    pub fn assemble(&self) -> Vec<i64> {
+        let mut addrs = Vec::with_capacity(self.ops.len() + 1);
+        let mut at = 0;
+        for op in self.ops.iter() {
+            addrs.push(at as i64);
+            at += op.size();
+        }
+        addrs.push(at as i64);
+        let seed_base = at as i64 + 1;
+        let scratch_base = seed_base + self.seeds.len() as i64;
+
+        let mut relative_base = 0;
+        let mut codes = Vec::with_capacity(scratch_base as usize + self.scratch_len());
+        for op in self.ops.iter() {
+            let param = |cell: &Cell| -> (i64, i64) {
+                let addr = match cell.region {
+                    Region::Seed => seed_base,
+                    Region::Scratch => scratch_base,
+                } + cell.index as i64;
+                if cell.relative {
+                    (2, addr - relative_base)
+                } else {
+                    (0, addr)
+                }
+            };
+            let src = |s: &Src| match s {
+                Src::Immediate(value) => (1, *value),
+                Src::Cell(cell) => param(cell),
+            };
+            let params: Vec<(i64, i64)> = match op {
+                Add(a, b, c) | Multiply(a, b, c) | LessThan(a, b, c) | Equals(a, b, c) => {
+                    vec![src(a), src(b), param(c)]
+                }
+                Input(c) => vec![param(c)],
+                Output(a) => vec![src(a)],
+                JumpTrue(a, label) | JumpFalse(a, label) => vec![src(a), (1, addrs[*label])],
+                SetRelativeBase(offset) => vec![(1, *offset)],
+            };
+            let modes: i64 = params
+                .iter()
+                .enumerate()
+                .map(|(i, (mode, _))| mode * 10_i64.pow(i as u32 + 2))
+                .sum();
+            codes.push(op.code() + modes);
+            codes.extend(params.iter().map(|(_, value)| value));
+            if let SetRelativeBase(offset) = op {
+                relative_base += offset;
+            }
+        }
+        codes.push(99);
+        codes.extend(self.seeds.iter());
+        codes.resize(codes.len() + self.scratch_len(), 0);
+        codes
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |values: &[i64]| {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        writeln!(f, "level:   {:?}", self.level())?;
+        writeln!(f, "program: {}", join(&self.assemble()))?;
+        write!(f, "inputs:  {}", join(&self.inputs))
+    }
+}
+
+struct Generator<'a> {
+    rng: &'a mut Rng,
+    level: Level,
+    seeds: usize,
+    scratch: usize,
+}
+
+impl<'a> Generator<'a> {
+    fn relative(&mut self) -> bool {
+        self.level >= Day09 && self.rng.one_in(2)
+    }
+
+    fn scratch(&mut self) -> Cell {
+        Cell {
+            region: Region::Scratch,
+            index: self.rng.below(self.scratch),
+            relative: self.relative(),
+        }
+    }
+
+    /// Any source. `bounded` leaves out scratch cells so that the operand
+    /// is at most `MAX_VALUE`, which keeps products and sums from growing
+    /// past `i32`.
+    fn src(&mut self, bounded: bool) -> Src {
+        let choices = if bounded { 2 } else { 3 };
+        match self.rng.below(choices) {
+            0 if self.level >= Day05 => Src::Immediate(self.rng.range(-MAX_VALUE, MAX_VALUE + 1)),
+            2 => Src::Cell(self.scratch()),
+            _ => Src::Cell(Cell {
+                region: Region::Seed,
+                index: self.rng.below(self.seeds),
+                relative: self.relative(),
+            }),
+        }
+    }
+
+    fn label(&mut self, at: usize, n: usize) -> usize {
+        at + 1 + self.rng.below(n - at)
+    }
+
+    fn op(&mut self, at: usize, n: usize) -> Op {
+        let kinds = match self.level {
+            Day02 => 2,
+            _ => 8,
+        };
+        match self.rng.below(kinds) {
+            0 => Add(self.src(false), self.src(true), self.scratch()),
+            1 => Multiply(self.src(true), self.src(true), self.scratch()),
+            2 => Input(self.scratch()),
+            3 => Output(self.src(false)),
+            4 => JumpTrue(self.src(false), self.label(at, n)),
+            5 => JumpFalse(self.src(false), self.label(at, n)),
+            6 => LessThan(self.src(false), self.src(false), self.scratch()),
+            _ => Equals(self.src(false), self.src(false), self.scratch()),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Differential fuzzer for the shared Intcode computer.
+//!
+//! Each case is a random program that is valid at some instruction-set
+//! level. It is run on a plain reference interpreter and on the shared
+//! `intcode` crate at every level that supports the case, and any
+//! disagreement in final memory, outputs or halting behaviour is reported,
+//! then shrunk by `minimize`.
+
+mod case;
+mod minimize;
+
+pub use case::{Case, Cell, Op, Region, Src};
+pub use intcode::{Level, Rng};
+pub use minimize::minimize;
+pub use targets::{all_targets, Outcome, Reference, Run, Shared, Target};
+
+use std::fmt;
+
+/// Generated programs only jump forward, so this is never reached by a
+/// correct interpreter.
+pub const STEP_LIMIT: usize = 10_000;
+
+const LEVELS: [Level; 3] = [Level::Day02, Level::Day05, Level::Day09];
+
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    pub case: Case,
+    pub runs: Vec<(&'static str, Run)>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.case)?;
+        for (name, run) in self.runs.iter() {
+            writeln!(
+                f,
+                "  {}: {:?} out={:?} mem={:?}",
+                name, run.outcome, run.outputs, run.memory
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Run `case` on every target that supports its level and compare results.
+pub fn check(targets: &[Box<dyn Target>], case: &Case) -> Option<Divergence> {
+    let program = case.assemble();
+    let level = case.level();
+    let runs: Vec<(&'static str, Run)> = targets
+        .iter()
+        .filter(|t| t.level() >= level)
+        .map(|t| {
+            let mut run = t.execute(&program, &case.inputs, STEP_LIMIT);
+            // memory is padded or grown differently; only the program itself
+            // is comparable
+            run.memory.truncate(program.len());
+            (t.name(), run)
+        })
+        .collect();
+    if runs.windows(2).all(|w| w[0].1.agrees(&w[1].1)) {
+        None
+    } else {
+        Some(Divergence {
+            case: case.clone(),
+            runs,
+        })
+    }
+}
+
+/// Check `iterations` cases. Case `i` is generated from `seed + i`, which is
+/// returned alongside each divergence so it can be replayed.
+pub fn fuzz(targets: &[Box<dyn Target>], seed: u64, iterations: usize) -> Vec<(u64, Divergence)> {
+    (0..iterations as u64)
+        .map(|i| seed.wrapping_add(i))
+        .filter_map(|case_seed| {
+            let mut rng = Rng::new(case_seed);
+            let level = LEVELS[rng.below(LEVELS.len())];
+            let case = Case::generate(&mut rng, level);
+            check(targets, &case).map(|d| (case_seed, d))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,30 @@
+use fuzz::{all_targets, fuzz, minimize};
+use std::env;
+use std::panic;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let seed: u64 = args.get(1).map_or(0, |s| s.parse().unwrap());
+    let iterations: usize = args.get(2).map_or(10_000, |s| s.parse().unwrap());
+
+    // crashes are expected and reported per case, not as they happen
+    panic::set_hook(Box::new(|_| {}));
+
+    let targets = all_targets();
+    let divergences = fuzz(&targets, seed, iterations);
+    for (case_seed, divergence) in divergences.iter() {
+        let minimal = minimize(&targets, divergence.clone());
+        println!("divergence at seed={}", case_seed);
+        println!("{}", minimal);
+    }
+    println!(
+        "{} cases from seed={}, {} divergences",
+        iterations,
+        seed,
+        divergences.len()
+    );
+    if !divergences.is_empty() {
+        process::exit(1);
+    }
+}
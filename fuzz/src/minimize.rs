@@ -0,0 +1,146 @@
+use crate::case::{Case, Op, Region, Src};
+use crate::{check, Divergence};
+use intcode::Level;
+use targets::Target;
+
+/// Greedily shrink a failing case until no single simplification still
+/// diverges. Every candidate is strictly smaller (fewer ops, inputs, cells or
+/// relative operands, or a value closer to zero) so this terminates.
+pub fn minimize(targets: &[Box<dyn Target>], divergence: Divergence) -> Divergence {
+    let mut best = divergence;
+    'outer: loop {
+        for candidate in shrinks(&best.case) {
+            if let Some(smaller) = check(targets, &candidate) {
+                best = smaller;
+                continue 'outer;
+            }
+        }
+        return best;
+    }
+}
+
+fn smaller_values(value: i64) -> Vec<i64> {
+    match value {
+        0 => vec![],
+        v if v / 2 == 0 => vec![0],
+        v => vec![0, v / 2],
+    }
+}
+
+fn shrinks(case: &Case) -> Vec<Case> {
+    let mut candidates = Vec::new();
+
+    // drop whole instructions, retargeting jumps past them
+    for i in 0..case.ops.len() {
+        let mut c = case.clone();
+        c.ops.remove(i);
+        for op in c.ops.iter_mut() {
+            if let Some(label) = op.label_mut() {
+                if *label > i {
+                    *label -= 1;
+                }
+            }
+        }
+        if c.inputs.len() > c.input_ops() {
+            c.inputs.pop();
+        }
+        candidates.push(c);
+    }
+
+    // drop surplus inputs
+    if case.inputs.len() > case.input_ops() {
+        for i in 0..case.inputs.len() {
+            let mut c = case.clone();
+            c.inputs.remove(i);
+            candidates.push(c);
+        }
+    }
+
+    // drop unused data cells
+    if let Some(c) = compact(case) {
+        candidates.push(c);
+    }
+
+    // simplify operands
+    let immediates = case.level() >= Level::Day05;
+    for i in 0..case.ops.len() {
+        for j in 0..case.ops[i].sources().len() {
+            let mut replacements = Vec::new();
+            match case.ops[i].sources()[j] {
+                Src::Immediate(v) => {
+                    replacements.extend(smaller_values(v).into_iter().map(Src::Immediate))
+                }
+                Src::Cell(_) if immediates => replacements.push(Src::Immediate(0)),
+                Src::Cell(_) => {}
+            }
+            for replacement in replacements {
+                let mut c = case.clone();
+                *c.ops[i].sources_mut()[j] = replacement;
+                candidates.push(c);
+            }
+        }
+        for j in 0..case.ops[i].cells().len() {
+            if case.ops[i].cells()[j].relative {
+                let mut c = case.clone();
+                c.ops[i].cells_mut()[j].relative = false;
+                candidates.push(c);
+            }
+        }
+        if let Op::SetRelativeBase(v) = case.ops[i] {
+            for smaller in smaller_values(v) {
+                let mut c = case.clone();
+                c.ops[i] = Op::SetRelativeBase(smaller);
+                candidates.push(c);
+            }
+        }
+    }
+
+    // pull values towards zero
+    for i in 0..case.seeds.len() {
+        for smaller in smaller_values(case.seeds[i]) {
+            let mut c = case.clone();
+            c.seeds[i] = smaller;
+            candidates.push(c);
+        }
+    }
+    for i in 0..case.inputs.len() {
+        for smaller in smaller_values(case.inputs[i]) {
+            let mut c = case.clone();
+            c.inputs[i] = smaller;
+            candidates.push(c);
+        }
+    }
+
+    candidates
+}
+
+/// Renumber seed and scratch cells so that only referenced ones remain.
+fn compact(case: &Case) -> Option<Case> {
+    let used = |region: Region, n: usize| -> Vec<usize> {
+        (0..n)
+            .filter(|&i| {
+                case.ops
+                    .iter()
+                    .flat_map(|op| op.cells())
+                    .any(|cell| cell.region == region && cell.index == i)
+            })
+            .collect()
+    };
+    let seeds = used(Region::Seed, case.seeds.len());
+    let scratch = used(Region::Scratch, case.scratch_len());
+    if seeds.len() == case.seeds.len() && scratch.len() == case.scratch_len() {
+        return None;
+    }
+    let mut c = case.clone();
+    c.seeds = seeds.iter().map(|&i| case.seeds[i]).collect();
+    for op in c.ops.iter_mut() {
+        for cell in op.cells_mut() {
+            let kept = match cell.region {
+                Region::Seed => &seeds,
+                Region::Scratch => &scratch,
+            };
+            cell.index = kept.iter().position(|&i| i == cell.index).unwrap();
+        }
+    }
+    Some(c)
+}
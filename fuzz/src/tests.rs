@@ -0,0 +1,115 @@
+use super::*;
+
+fn seed(index: usize) -> Cell {
+    Cell {
+        region: Region::Seed,
+        index,
+        relative: false,
+    }
+}
+
+fn scratch(index: usize) -> Cell {
+    Cell {
+        region: Region::Scratch,
+        index,
+        relative: false,
+    }
+}
+
+/// The shared crate with every multiply patched into an add.
+struct Broken;
+
+impl Target for Broken {
+    fn name(&self) -> &'static str {
+        "broken"
+    }
+
+    fn level(&self) -> Level {
+        Level::Day09
+    }
+
+    fn execute(&self, program: &[i64], inputs: &[i64], limit: usize) -> Run {
+        let mut patched = program.to_vec();
+        let mut eip = 0;
+        while patched[eip] != 99 {
+            let len = match patched[eip] % 100 {
+                1 | 2 | 7 | 8 => 4,
+                5 | 6 => 3,
+                _ => 2,
+            };
+            if patched[eip] % 100 == 2 {
+                patched[eip] -= 1;
+            }
+            eip += len;
+        }
+        Shared(Level::Day09).execute(&patched, inputs, limit)
+    }
+}
+
+#[test]
+fn test_assemble() {
+    let case = Case {
+        ops: vec![
+            Op::Add(Src::Cell(seed(0)), Src::Cell(seed(1)), scratch(0)),
+            Op::Multiply(Src::Cell(scratch(0)), Src::Cell(seed(0)), scratch(0)),
+        ],
+        seeds: vec![3, 4],
+        inputs: vec![],
+    };
+    assert_eq!(case.level(), Level::Day02);
+    assert_eq!(
+        case.assemble(),
+        vec![1, 9, 10, 11, 2, 11, 9, 11, 99, 3, 4, 0]
+    );
+
+    let case = Case {
+        ops: vec![
+            Op::SetRelativeBase(10),
+            Op::Input(Cell {
+                relative: true,
+                ..scratch(0)
+            }),
+            Op::JumpTrue(Src::Immediate(1), 4),
+            Op::Output(Src::Cell(scratch(0))),
+        ],
+        seeds: vec![],
+        inputs: vec![7],
+    };
+    assert_eq!(case.level(), Level::Day09);
+    assert_eq!(
+        case.assemble(),
+        vec![109, 10, 203, 0, 1105, 1, 9, 4, 10, 99, 0]
+    );
+}
+
+#[test]
+fn test_generated_levels() {
+    for (i, &level) in LEVELS.iter().cycle().take(300).enumerate() {
+        let case = Case::generate(&mut Rng::new(i as u64), level);
+        assert!(case.level() <= level);
+        assert_eq!(case.inputs.len(), case.input_ops());
+    }
+}
+
+#[test]
+fn test_interpreters_agree() {
+    let divergences = fuzz(&all_targets(), 0, 500);
+    for (_, divergence) in divergences.iter() {
+        println!("{}", divergence);
+    }
+    assert!(divergences.is_empty());
+}
+
+#[test]
+fn test_minimize() {
+    let targets: Vec<Box<dyn Target>> = vec![Box::new(Shared(Level::Day09)), Box::new(Broken)];
+    let (_, divergence) = fuzz(&targets, 0, 100).into_iter().next().unwrap();
+    let minimal = minimize(&targets, divergence.clone());
+    assert!(check(&targets, &minimal.case).is_some());
+    assert_eq!(minimal.case.ops.len(), 1);
+    match minimal.case.ops[0] {
+        Op::Multiply(..) => {}
+        op => panic!("expected a multiply, got {:?}", op),
+    }
+    assert!(minimal.case.inputs.is_empty());
+}
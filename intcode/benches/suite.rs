@@ -0,0 +1,264 @@
+//! Benchmarks for the VM and the day solvers, run against the puzzle inputs
+//! in the repository.
+//!
+//! ```text
+//! cargo bench -p intcode --bench suite -- [filter] [--save FILE] [--baseline FILE]
+//! ```
+//!
+//! Each case is timed over several samples and reported as the median and
+//! fastest time per iteration. `--save` writes the results as tab-separated
+//! `name median_ns min_ns iterations` lines, and `--baseline` compares this
+//! run against such a file. `benches/baseline.tsv` is a saved reference run.
+
+use intcode::{parse_program, Computer, Level, Memory, PhaseSearch, Topology, C};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const DAY02: &str = include_str!("../../day02/input.txt");
+const DAY07: &str = include_str!("../../day07/input.txt");
+const DAY09: &str = include_str!("../../day09/input.txt");
+
+const SAMPLES: usize = 10;
+/// Roughly how long each sample should take.
+const SAMPLE_TIME: Duration = Duration::from_millis(50);
+
+struct Measurement {
+    name: String,
+    median: Duration,
+    min: Duration,
+    iterations: u32,
+}
+
+struct Suite {
+    filter: Option<String>,
+    results: Vec<Measurement>,
+}
+
+impl Suite {
+    /// Time `f`, first finding how many calls fill a sample.
+    fn bench<F: FnMut()>(&mut self, name: &str, mut f: F) {
+        if self
+            .filter
+            .as_ref()
+            .is_some_and(|filter| !name.contains(filter.as_str()))
+        {
+            return;
+        }
+        let start = Instant::now();
+        f();
+        let once = start.elapsed().max(Duration::from_nanos(1));
+        let iterations = (SAMPLE_TIME.as_nanos() / once.as_nanos()).clamp(1, 1_000_000) as u32;
+        let mut samples: Vec<Duration> = (0..SAMPLES)
+            .map(|_| {
+                let start = Instant::now();
+                for _ in 0..iterations {
+                    f();
+                }
+                start.elapsed() / iterations
+            })
+            .collect();
+        samples.sort();
+        let measurement = Measurement {
+            name: name.to_string(),
+            median: samples[SAMPLES / 2],
+            min: samples[0],
+            iterations,
+        };
+        println!(
+            "{:<36} {:>12.2?} (min {:.2?}, {} x {})",
+            measurement.name, measurement.median, measurement.min, SAMPLES, iterations
+        );
+        self.results.push(measurement);
+    }
+
+    fn save(&self, path: &str) {
+        let lines: Vec<String> = self
+            .results
+            .iter()
+            .map(|m| {
+                format!(
+                    "{}\t{}\t{}\t{}",
+                    m.name,
+                    m.median.as_nanos(),
+                    m.min.as_nanos(),
+                    m.iterations
+                )
+            })
+            .collect();
+        fs::write(path, lines.join("\n") + "\n").expect("Can't save results");
+    }
+
+    /// Print each median's change from the one in the baseline file.
+    fn compare(&self, path: &str) {
+        let text = fs::read_to_string(path).expect("Can't read baseline");
+        let baseline: BTreeMap<&str, f64> = text
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                Some((fields.next()?, fields.next()?.parse().ok()?))
+            })
+            .collect();
+        println!("\nagainst {}:", path);
+        for m in self.results.iter() {
+            match baseline.get(m.name.as_str()) {
+                Some(&before) => {
+                    let change = (m.median.as_nanos() as f64 / before - 1.0) * 100.0;
+                    println!("{:<36} {:>+8.1}%", m.name, change);
+                }
+                None => println!("{:<36} {:>9}", m.name, "new"),
+            }
+        }
+    }
+}
+
+fn program(text: &str) -> Memory {
+    parse_program(text).expect("Bad puzzle input")
+}
+
+/// Counts `n` down to zero through relative, immediate and position modes.
+fn countdown(n: C) -> Memory {
+    vec![
+        109, 20, // arb 20
+        1101, 0, n, 30, // add 0, n, [30]
+        21201, 10, -1, 10, // add [rb+10], -1, [rb+10]
+        1205, 10, 6,  // jt [rb+10], 6
+        99, // halt
+    ]
+}
+
+/// The day 2 noun/verb search, cloning one prepared computer per guess.
+fn sweep(base: &Computer) -> Option<C> {
+    for noun in 0..100 {
+        for verb in 0..100 {
+            let mut candidate = base.clone();
+            candidate.set(1, noun);
+            candidate.set(2, verb);
+            candidate.run_to_halt();
+            if candidate.memory()[0] == 19690720 {
+                return Some(100 * noun + verb);
+            }
+        }
+    }
+    None
+}
+
+/// The same search as day 2 did it before computers shared memory: copy
+/// the program, build a computer and run a copy of that for every guess.
+fn sweep_rebuilding(codes: &[C]) -> Option<C> {
+    for noun in 0..100 {
+        for verb in 0..100 {
+            let mut candidate = codes.to_vec();
+            candidate[1] = noun;
+            candidate[2] = verb;
+            let result = Computer::with_level(candidate, Level::Day02).run();
+            if result.memory()[0] == 19690720 {
+                return Some(100 * noun + verb);
+            }
+        }
+    }
+    None
+}
+
+fn main() {
+    let mut filter = None;
+    let mut save = None;
+    let mut baseline = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save" => save = args.next(),
+            "--baseline" => baseline = args.next(),
+            // passed by `cargo bench`
+            "--bench" => {}
+            _ => filter = Some(arg),
+        }
+    }
+    let mut suite = Suite {
+        filter,
+        results: Vec::new(),
+    };
+
+    let countdown = Computer::new(countdown(100_000));
+    suite.bench("decode/countdown 100k", || {
+        let mut computer = countdown.clone();
+        black_box(computer.run_to_halt());
+    });
+
+    let boost = Computer::new(program(DAY09));
+    for &(mode, name) in [(1, "day09/boost test"), (2, "day09/boost")].iter() {
+        suite.bench(name, || {
+            let mut computer = boost.clone();
+            computer.queue_inputs(Some(mode));
+            computer.run_to_halt();
+            black_box(computer.outputs);
+        });
+    }
+
+    let amplifier = Computer::with_level(program(DAY07), Level::Day05);
+    for (domain, name) in [
+        ([0, 1, 2, 3, 4], "day07/phase search part 1"),
+        ([5, 6, 7, 8, 9], "day07/phase search part 2"),
+    ]
+    .iter()
+    {
+        // one thread, so results don't depend on the machine's core count
+        suite.bench(name, || {
+            let search =
+                PhaseSearch::new(amplifier.clone(), domain, Topology::ring(5)).with_threads(1);
+            black_box(search.run().unwrap());
+        });
+    }
+
+    let mut gravity = program(DAY02);
+    gravity[1] = 12;
+    gravity[2] = 2;
+    let base = Computer::with_level(gravity.clone(), Level::Day02);
+    assert_eq!(sweep(&base), sweep_rebuilding(&gravity));
+    suite.bench("day02/noun-verb sweep", || {
+        black_box(sweep(black_box(&base)));
+    });
+    suite.bench("day02/noun-verb sweep rebuilding", || {
+        black_box(sweep_rebuilding(black_box(&gravity)));
+    });
+
+    suite.bench("clone/machine", || {
+        black_box(black_box(&boost).clone());
+    });
+    suite.bench("clone/machine and write", || {
+        let mut clone = black_box(&boost).clone();
+        clone.set(1500, 1);
+        black_box(clone);
+    });
+    suite.bench("clone/copy memory", || {
+        black_box(black_box(&boost).memory().to_vec());
+    });
+    // breadth-first fan-out, as in a search: clone every state and run each
+    // child a little
+    suite.bench("clone/fan-out 4^4", || {
+        let mut frontier = vec![boost.clone()];
+        for _ in 0..4 {
+            frontier = frontier
+                .iter()
+                .flat_map(|computer| {
+                    (1..=4).map(move |input| {
+                        let mut child = computer.clone();
+                        child.queue_inputs(Some(input));
+                        child.run_steps(50);
+                        child
+                    })
+                })
+                .collect();
+        }
+        black_box(frontier);
+    });
+
+    if let Some(path) = save {
+        suite.save(&path);
+    }
+    if let Some(path) = baseline {
+        suite.compare(&path);
+    }
+}
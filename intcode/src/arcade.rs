@@ -0,0 +1,130 @@
+use crate::{Computer, Display, Memory, Palette, State, C};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const PADDLE: C = 3;
+const BALL: C = 4;
+
+/// Decides which way to tilt the joystick: -1 left, 0 neutral, 1 right.
+pub trait Joystick {
+    fn tilt(&mut self, screen: &Display) -> C;
+}
+
+impl<F: FnMut(&Display) -> C> Joystick for F {
+    fn tilt(&mut self, screen: &Display) -> C {
+        self(screen)
+    }
+}
+
+/// Keeps the paddle under the ball.
+#[derive(Debug, Clone, Default)]
+pub struct BallTracker;
+
+impl Joystick for BallTracker {
+    fn tilt(&mut self, screen: &Display) -> C {
+        match (screen.find(BALL).next(), screen.find(PADDLE).next()) {
+            (Some((ball, _)), Some((paddle, _))) => (ball - paddle).signum(),
+            _ => 0,
+        }
+    }
+}
+
+/// How a game ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub state: State,
+    pub score: Option<C>,
+    /// Screens shown: one per joystick read plus the last one.
+    pub frames: usize,
+}
+
+/// Runs an arcade cabinet program, drawing its (x, y, tile) output on a
+/// `Display` and reading the joystick from a `Joystick`.
+#[derive(Debug, Clone)]
+pub struct Arcade {
+    pub computer: Computer,
+    pub screen: Display,
+    pub frame_limit: Option<usize>,
+    replay: Option<Vec<String>>,
+}
+
+impl Arcade {
+    pub fn new(program: Memory) -> Self {
+        Arcade {
+            computer: Computer::new(program),
+            screen: Display::new(),
+            frame_limit: None,
+            replay: None,
+        }
+    }
+
+    /// Overwrite memory before the game starts, e.g. `&[(0, 2)]`.
+    pub fn with_patches(mut self, patches: &[(usize, C)]) -> Self {
+        for &(addr, value) in patches {
+            self.computer.set(addr, value);
+        }
+        self
+    }
+
+    /// Insert two quarters, which the cabinets read from address 0.
+    pub fn free_play(self) -> Self {
+        self.with_patches(&[(0, 2)])
+    }
+
+    pub fn with_frame_limit(mut self, frames: usize) -> Self {
+        self.frame_limit = Some(frames);
+        self
+    }
+
+    /// Keep a rendering of every frame for `write_replay`.
+    pub fn recording(mut self) -> Self {
+        self.replay = Some(Vec::new());
+        self
+    }
+
+    fn show_frame(&mut self, frames: usize) {
+        if let Some(replay) = self.replay.as_mut() {
+            replay.push(format!(
+                "frame {} score {}\n{}",
+                frames,
+                self.screen.score.unwrap_or(0),
+                self.screen.render(&Palette::arcade())
+            ));
+        }
+    }
+
+    /// Play until the program halts, traps or hits the frame limit.
+    pub fn play<J: Joystick + ?Sized>(&mut self, joystick: &mut J) -> Game {
+        let mut frames = 0;
+        loop {
+            self.computer.run_to_halt();
+            self.screen.update(&mut self.computer);
+            frames += 1;
+            self.show_frame(frames);
+            let limited = self.frame_limit.is_some_and(|limit| frames >= limit);
+            if self.computer.state != State::AwaitingInput || limited {
+                return Game {
+                    state: self.computer.state.clone(),
+                    score: self.screen.score,
+                    frames,
+                };
+            }
+            let tilt = joystick.tilt(&self.screen);
+            self.computer.queue_inputs(Some(tilt));
+        }
+    }
+
+    /// Every recorded frame, separated by blank lines.
+    pub fn write_replay<W: Write>(&self, out: W) -> io::Result<()> {
+        let mut out = out;
+        for frame in self.replay.iter().flatten() {
+            writeln!(out, "{}", frame)?;
+        }
+        out.flush()
+    }
+
+    pub fn save_replay<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_replay(BufWriter::new(File::create(path)?))
+    }
+}
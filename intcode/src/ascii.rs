@@ -0,0 +1,111 @@
+use crate::{Computer, Event, State, C};
+use std::io::{self, BufRead, Write};
+use std::iter::FromIterator;
+
+/// Output read back from an ASCII program. Values outside the ASCII range,
+/// usually a final numeric answer, are kept apart from the text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Text {
+    pub text: String,
+    pub values: Vec<C>,
+}
+
+impl Text {
+    fn push(&mut self, value: C) {
+        if (0..128).contains(&value) {
+            self.text.push(value as u8 as char);
+        } else {
+            self.values.push(value);
+        }
+    }
+}
+
+impl FromIterator<C> for Text {
+    fn from_iter<I: IntoIterator<Item = C>>(values: I) -> Self {
+        let mut text = Text::default();
+        for value in values {
+            text.push(value);
+        }
+        text
+    }
+}
+
+/// Drives a computer that talks in lines of ASCII text.
+#[derive(Debug, Clone)]
+pub struct Ascii {
+    pub computer: Computer,
+}
+
+impl Ascii {
+    pub fn new(computer: Computer) -> Self {
+        Ascii { computer }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.computer.state
+    }
+
+    /// Queue `line` and a newline after any input already waiting.
+    pub fn send_line(&mut self, line: &str) {
+        self.computer
+            .queue_inputs(line.bytes().chain(Some(b'\n')).map(C::from));
+        self.computer.resume();
+    }
+
+    /// Run until the machine stops, collecting everything it printed.
+    pub fn run(&mut self) -> Text {
+        self.read_until("")
+    }
+
+    /// Run until the text printed so far ends with `prompt` or the machine
+    /// stops. An empty prompt only stops with the machine.
+    pub fn read_until(&mut self, prompt: &str) -> Text {
+        let mut text = Text::default();
+        while let Event::Output(_) = self.computer.run_to_event() {
+            for value in self.computer.outputs.drain(..) {
+                text.push(value);
+            }
+            if !prompt.is_empty() && text.text.ends_with(prompt) {
+                break;
+            }
+        }
+        text
+    }
+
+    pub fn read_line(&mut self) -> Text {
+        self.read_until("\n")
+    }
+
+    /// Bridge the machine to a terminal: print what it writes, feed it a line
+    /// of `input` whenever it waits. Returns once it stops for any other
+    /// reason or `input` runs out.
+    pub fn interact<R: BufRead, W: Write>(
+        &mut self,
+        mut input: R,
+        mut output: W,
+    ) -> io::Result<State> {
+        loop {
+            let text = self.run();
+            output.write_all(text.text.as_bytes())?;
+            for value in text.values {
+                writeln!(output, "{}", value)?;
+            }
+            output.flush()?;
+            if self.computer.state != State::AwaitingInput {
+                return Ok(self.computer.state.clone());
+            }
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(self.computer.state.clone());
+            }
+            self.send_line(line.trim_end_matches(&['\r', '\n'][..]));
+        }
+    }
+
+    /// `interact` on stdin and stdout.
+    pub fn interactive(&mut self) -> io::Result<State> {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        self.interact(stdin.lock(), stdout.lock())
+    }
+}
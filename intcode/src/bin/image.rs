@@ -0,0 +1,73 @@
+//! Converts Intcode programs between text and the binary image format.
+//!
+//! ```text
+//! intcode-image pack <program.txt> <program.icim> [level] [name]
+//! intcode-image unpack <program.icim> [program.txt]
+//! intcode-image info <program.icim>
+//! ```
+
+use intcode::{Image, Level};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::process;
+
+const USAGE: &str = "usage: intcode-image pack <text> <image> [level] [name]
+       intcode-image unpack <image> [text]
+       intcode-image info <image>";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn parse_level(text: &str) -> Level {
+    match text {
+        "2" => Level::Day02,
+        "5" => Level::Day05,
+        "9" => Level::Day09,
+        _ => usage(),
+    }
+}
+
+fn convert(args: &[&str]) -> Result<(), Box<dyn Error>> {
+    match *args {
+        ["pack", text, image, ref rest @ ..] if rest.len() <= 2 => {
+            let mut packed = Image::from_text(&fs::read_to_string(text)?)?;
+            if let Some(level) = rest.first() {
+                packed = packed.with_level(parse_level(level));
+            }
+            if let Some(name) = rest.get(1) {
+                packed = packed.with_name(name);
+            }
+            packed.save(image)?;
+        }
+        ["unpack", image] => println!("{}", Image::load(image)?.to_text()),
+        ["unpack", image, text] => fs::write(text, Image::load(image)?.to_text() + "\n")?,
+        ["info", image] => {
+            let image = Image::load(image)?;
+            println!("name: {}", image.name.as_deref().unwrap_or("-"));
+            match image.level {
+                Some(level) => println!("level: {:?}", level),
+                None => println!("level: -"),
+            }
+            println!("entry: {}", image.entry);
+            println!("words: {}", image.memory.len());
+            match image.source_hash {
+                Some(hash) => println!("source hash: {:016x}", hash),
+                None => println!("source hash: -"),
+            }
+        }
+        _ => usage(),
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if let Err(e) = convert(&args) {
+        eprintln!("image: {}", e);
+        process::exit(1);
+    }
+}
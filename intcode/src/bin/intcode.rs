@@ -0,0 +1,281 @@
+//! Runs an Intcode program from a text or image file.
+//!
+//! Exits with 0 when the program halts, 3 when it is left waiting for
+//! input, 4 when it hits the step limit, 2 for bad arguments and 1 for any
+//! other error, including a trap.
+
+use intcode::{parse_program, Computer, Image, Level, LoadError, Memory, State, Text, C, MAGIC};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+const USAGE: &str = "usage: intcode <program> [options]
+
+  -i, --input VALUES      queue numbers, e.g. 1 or 5,0
+      --input-file PATH   queue numbers read from a file
+  -a, --ascii LINE        queue a line of text and a newline
+  -p, --patch PATCHES     set memory first, e.g. 1=12,2=2
+  -l, --level 2|5|9       instruction set to allow (default 9)
+  -s, --steps N           stop after N instructions
+  -o, --output FORMAT     print outputs as numbers (default) or ascii
+  -m, --memory ADDRS      print memory at these addresses afterwards, e.g. 0
+  -t, --trace             print each instruction executed to stderr
+      --profile           print instruction counts to stderr";
+
+const HALTED: i32 = 0;
+const ERROR: i32 = 1;
+const USAGE_ERROR: i32 = 2;
+const BLOCKED: i32 = 3;
+const STEP_LIMIT: i32 = 4;
+
+#[derive(Debug, Default)]
+struct Options {
+    program: String,
+    inputs: Vec<C>,
+    patches: Vec<(usize, C)>,
+    peek: Vec<usize>,
+    level: Option<Level>,
+    steps: Option<usize>,
+    ascii: bool,
+    trace: bool,
+    profile: bool,
+}
+
+fn usage(message: &str) -> ! {
+    eprintln!("intcode: {}\n\n{}", message, USAGE);
+    process::exit(USAGE_ERROR);
+}
+
+fn parse_values(text: &str) -> Result<Memory, LoadError> {
+    match parse_program(text) {
+        Err(LoadError::Empty) => Ok(Vec::new()),
+        result => result,
+    }
+}
+
+fn parse_patches(text: &str) -> Option<Vec<(usize, C)>> {
+    text.split(',')
+        .map(|patch| {
+            let (addr, value) = patch.split_once('=')?;
+            Some((addr.trim().parse().ok()?, value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+fn parse_args(args: &[String]) -> Result<Options, Box<dyn Error>> {
+    let mut options = Options::default();
+    let mut program = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || match args.next() {
+            Some(value) => value.as_str(),
+            None => usage(&format!("{} needs a value", arg)),
+        };
+        match arg.as_str() {
+            "-i" | "--input" => options.inputs.extend(parse_values(value())?),
+            "--input-file" => options
+                .inputs
+                .extend(parse_values(&fs::read_to_string(value())?)?),
+            "-a" | "--ascii" => options
+                .inputs
+                .extend(value().bytes().chain(Some(b'\n')).map(C::from)),
+            "-p" | "--patch" => match parse_patches(value()) {
+                Some(patches) => options.patches.extend(patches),
+                None => usage("patches look like 1=12,2=2"),
+            },
+            "-l" | "--level" => {
+                options.level = Some(match value() {
+                    "2" => Level::Day02,
+                    "5" => Level::Day05,
+                    "9" => Level::Day09,
+                    _ => usage("the level is 2, 5 or 9"),
+                })
+            }
+            "-s" | "--steps" => match value().parse() {
+                Ok(steps) => options.steps = Some(steps),
+                Err(_) => usage("the step limit is a number"),
+            },
+            "-o" | "--output" => {
+                options.ascii = match value() {
+                    "numbers" => false,
+                    "ascii" => true,
+                    _ => usage("the output is numbers or ascii"),
+                }
+            }
+            "-m" | "--memory" => {
+                for addr in value().split(',') {
+                    match addr.trim().parse() {
+                        Ok(addr) => options.peek.push(addr),
+                        Err(_) => usage("memory addresses are numbers"),
+                    }
+                }
+            }
+            "-t" | "--trace" => options.trace = true,
+            "--profile" => options.profile = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(HALTED);
+            }
+            flag if flag.starts_with('-') && flag != "-" => {
+                usage(&format!("unknown option {}", flag))
+            }
+            path if program.is_none() => program = Some(path.to_string()),
+            _ => usage("only one program can be run"),
+        }
+    }
+    match program {
+        Some(program) => options.program = program,
+        None => usage("no program given"),
+    }
+    Ok(options)
+}
+
+/// Load a text program or, going by its header, an image.
+fn load(options: &Options) -> Result<Computer, Box<dyn Error>> {
+    let bytes = if options.program == "-" {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes)?;
+        bytes
+    } else {
+        fs::read(&options.program)?
+    };
+    let mut image = if bytes.starts_with(&MAGIC) {
+        Image::from_bytes(&bytes)?
+    } else {
+        Image::new(parse_program(&String::from_utf8(bytes)?)?)
+    };
+    if let Some(level) = options.level {
+        image = image.with_level(level);
+    }
+    let mut computer = image.computer();
+    for &(addr, value) in options.patches.iter() {
+        if addr >= computer.memory().len() {
+            return Err(format!("patch address {} is out of range", addr).into());
+        }
+        computer.set(addr, value);
+    }
+    computer.queue_inputs(options.inputs.iter().cloned());
+    Ok(computer)
+}
+
+/// Run within the step limit, tracing and counting instructions if asked.
+fn execute(computer: &mut Computer, options: &Options) {
+    if !options.trace && !options.profile {
+        match options.steps {
+            Some(steps) => computer.run_steps(steps),
+            None => computer.run_to_halt(),
+        };
+        return;
+    }
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut steps = 0;
+    computer.resume();
+    while computer.state == State::Running && options.steps.map_or(true, |limit| steps < limit) {
+        let eip = computer.eip;
+        computer.step_mut();
+        steps += 1;
+        if let State::Trapped(_) | State::Fault(_) = computer.state {
+            break;
+        }
+        let name = computer.instructions.format(&computer.last);
+        if options.trace {
+            eprintln!("{:>8} {:04} {}", computer.counter, eip, name);
+        }
+        if options.profile {
+            let opcode = name.split(' ').next().unwrap_or_default().to_string();
+            *counts.entry(opcode).or_insert(0) += 1;
+        }
+    }
+    if options.profile {
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        eprintln!("{} instructions", steps);
+        for (opcode, count) in counts {
+            eprintln!("{:>10} {}", count, opcode);
+        }
+    }
+}
+
+fn print_outputs(computer: &Computer, options: &Options) {
+    if options.ascii {
+        let text: Text = computer.outputs.iter().cloned().collect();
+        print!("{}", text.text);
+        for value in text.values {
+            println!("{}", value);
+        }
+    } else {
+        for value in computer.outputs.iter() {
+            println!("{}", value);
+        }
+    }
+    for &addr in options.peek.iter() {
+        match computer.memory().get(addr) {
+            Some(value) => println!("[{}] = {}", addr, value),
+            None => println!("[{}] out of range", addr),
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => usage(&e.to_string()),
+    };
+    let mut computer = match load(&options) {
+        Ok(computer) => computer,
+        Err(e) => {
+            eprintln!("intcode: {}: {}", options.program, e);
+            process::exit(ERROR);
+        }
+    };
+    execute(&mut computer, &options);
+    print_outputs(&computer, &options);
+    process::exit(match &computer.state {
+        State::Halted => HALTED,
+        State::AwaitingInput => {
+            eprintln!("intcode: waiting for input at eip={}", computer.eip);
+            BLOCKED
+        }
+        State::Running => {
+            eprintln!("intcode: stopped after {} steps", computer.counter);
+            STEP_LIMIT
+        }
+        State::Trapped(violation) => {
+            eprintln!("intcode: trapped at eip={}: {}", computer.eip, violation);
+            ERROR
+        }
+        State::Fault(error) => {
+            eprintln!("intcode: fault at eip={}: {}", computer.eip, error);
+            ERROR
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args: Vec<String> = "prog.txt -i 1,2 -a hi -p 1=12,2=2 -l 5 -s 10 -o ascii -m 0"
+            .split(' ')
+            .map(String::from)
+            .collect();
+        let options = parse_args(&args).unwrap();
+        assert_eq!(options.program, "prog.txt");
+        assert_eq!(options.inputs, vec![1, 2, 104, 105, 10]);
+        assert_eq!(options.patches, vec![(1, 12), (2, 2)]);
+        assert_eq!(options.level, Some(Level::Day05));
+        assert_eq!(options.steps, Some(10));
+        assert_eq!(options.peek, vec![0]);
+        assert!(options.ascii && !options.trace && !options.profile);
+
+        assert_eq!(parse_patches(" 0 = -1"), Some(vec![(0, -1)]));
+        assert_eq!(parse_patches("1=12,2"), None);
+        assert_eq!(parse_values("# none\n").unwrap(), Vec::<C>::new());
+    }
+}
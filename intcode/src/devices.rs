@@ -0,0 +1,241 @@
+use crate::rng::Rng;
+use crate::C;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Lets boxed devices be cloned along with the computer they are attached
+/// to, and looked up again by type. Implemented for every `Clone` device.
+pub trait DeviceClone {
+    fn clone_box(&self) -> Box<dyn Device>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Device + Clone + 'static> DeviceClone for T {
+    fn clone_box(&self) -> Box<dyn Device> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Something mapped into the address space. Offsets are relative to the
+/// start of the device's range.
+pub trait Device: DeviceClone + Send + Sync {
+    fn read(&mut self, offset: usize) -> C;
+    fn write(&mut self, offset: usize, value: C);
+
+    /// Called after every instruction the computer executes.
+    fn tick(&mut self) {}
+}
+
+struct Mapping {
+    start: usize,
+    len: usize,
+    device: Box<dyn Device>,
+}
+
+impl Clone for Mapping {
+    fn clone(&self) -> Self {
+        Mapping {
+            start: self.start,
+            len: self.len,
+            device: self.device.clone_box(),
+        }
+    }
+}
+
+/// Routes reads and writes in mapped ranges to devices instead of memory.
+/// Instructions are always fetched from memory.
+#[derive(Clone, Default)]
+pub struct Bus {
+    mappings: Vec<Mapping>,
+}
+
+impl fmt::Debug for Bus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges: Vec<_> = self
+            .mappings
+            .iter()
+            .map(|m| m.start..m.start + m.len)
+            .collect();
+        write!(f, "Bus{:?}", ranges)
+    }
+}
+
+impl Bus {
+    /// Map `device` over `len` addresses starting at `start`.
+    ///
+    /// Panics if the range overlaps another device.
+    pub fn map<D: Device + 'static>(&mut self, start: usize, len: usize, device: D) {
+        assert!(len > 0, "Can't map an empty range");
+        assert!(
+            self.mappings
+                .iter()
+                .all(|m| start + len <= m.start || m.start + m.len <= start),
+            "Range {}..{} overlaps another device",
+            start,
+            start + len
+        );
+        self.mappings.push(Mapping {
+            start,
+            len,
+            device: Box::new(device),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    fn find(&mut self, addr: usize) -> Option<(&mut Box<dyn Device>, usize)> {
+        self.mappings
+            .iter_mut()
+            .find(|m| m.start <= addr && addr < m.start + m.len)
+            .map(|m| (&mut m.device, addr - m.start))
+    }
+
+    /// The device mapped at `start`, if it is a `T`.
+    pub fn get<T: Device + 'static>(&self, start: usize) -> Option<&T> {
+        self.mappings
+            .iter()
+            .find(|m| m.start == start)
+            .and_then(|m| m.device.as_any().downcast_ref())
+    }
+
+    pub fn get_mut<T: Device + 'static>(&mut self, start: usize) -> Option<&mut T> {
+        self.mappings
+            .iter_mut()
+            .find(|m| m.start == start)
+            .and_then(|m| m.device.as_any_mut().downcast_mut())
+    }
+
+    /// `None` if nothing is mapped at `addr`.
+    pub(crate) fn read(&mut self, addr: usize) -> Option<C> {
+        self.find(addr).map(|(device, offset)| device.read(offset))
+    }
+
+    /// `false` if nothing is mapped at `addr`.
+    pub(crate) fn write(&mut self, addr: usize, value: C) -> bool {
+        match self.find(addr) {
+            Some((device, offset)) => {
+                device.write(offset, value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn tick(&mut self) {
+        for mapping in self.mappings.iter_mut() {
+            mapping.device.tick();
+        }
+    }
+}
+
+/// Counts executed instructions. Writing sets the count.
+#[derive(Debug, Clone, Default)]
+pub struct Timer {
+    pub ticks: C,
+}
+
+impl Device for Timer {
+    fn read(&mut self, _offset: usize) -> C {
+        self.ticks
+    }
+
+    fn write(&mut self, _offset: usize, value: C) {
+        self.ticks = value;
+    }
+
+    fn tick(&mut self) {
+        self.ticks += 1;
+    }
+}
+
+/// Each read returns a fresh pseudo-random non-negative number. Writing
+/// reseeds it.
+#[derive(Debug, Clone)]
+pub struct Entropy {
+    rng: Rng,
+}
+
+impl Entropy {
+    pub fn new(seed: u64) -> Self {
+        Entropy {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Device for Entropy {
+    fn read(&mut self, _offset: usize) -> C {
+        (self.rng.next_u64() >> 1) as C
+    }
+
+    fn write(&mut self, _offset: usize, value: C) {
+        self.rng = Rng::new(value as u64);
+    }
+}
+
+/// Characters in and out. Reading takes the next queued input character, or
+/// -1 if there is none; writing appends a character to `output`.
+#[derive(Debug, Clone, Default)]
+pub struct Console {
+    pub input: VecDeque<u8>,
+    pub output: String,
+}
+
+impl Device for Console {
+    fn read(&mut self, _offset: usize) -> C {
+        self.input.pop_front().map_or(-1, C::from)
+    }
+
+    fn write(&mut self, _offset: usize, value: C) {
+        self.output.push(value as u8 as char);
+    }
+}
+
+/// A grid of cells, one address per cell in row-major order.
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<C>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Framebuffer {
+            width,
+            height,
+            cells: vec![0; width * height],
+        }
+    }
+
+    /// Addresses to map it over.
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> C {
+        self.cells[y * self.width + x]
+    }
+}
+
+impl Device for Framebuffer {
+    fn read(&mut self, offset: usize) -> C {
+        self.cells[offset]
+    }
+
+    fn write(&mut self, offset: usize, value: C) {
+        self.cells[offset] = value;
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{InstructionSet, Memory, C};
+use std::fmt;
+
+/// One instruction, or one word that doesn't decode as an instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub addr: usize,
+    pub raw: Memory,
+    pub text: String,
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw: Vec<String> = self.raw.iter().map(|c| c.to_string()).collect();
+        write!(f, "{:04}  {:<24} {}", self.addr, raw.join(","), self.text)
+    }
+}
+
+/// Linear sweep from address 0. Intcode mixes code and data freely, so
+/// anything after the last reachable instruction may decode as nonsense;
+/// words that don't decode at all are listed as `data`.
+pub fn disassemble(memory: &[C], instructions: &InstructionSet) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut addr = 0;
+    while addr < memory.len() {
+        let line = match instructions.decode(memory, addr) {
+            Ok(instr) => Line {
+                addr,
+                text: instructions.format(&instr),
+                raw: instr.raw,
+            },
+            Err(_) => Line {
+                addr,
+                raw: vec![memory[addr]],
+                text: format!("data {}", memory[addr]),
+            },
+        };
+        addr += line.raw.len();
+        lines.push(line);
+    }
+    lines
+}
@@ -0,0 +1,170 @@
+use crate::{Computer, C};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// How each tile id is drawn as text and in images.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    tiles: BTreeMap<C, (char, [u8; 3])>,
+    /// Drawn for tile ids the palette doesn't know and for cells never drawn.
+    pub unknown: (char, [u8; 3]),
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            tiles: BTreeMap::new(),
+            unknown: ('?', [255, 0, 255]),
+        }
+    }
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Palette::default()
+    }
+
+    /// Empty, wall, block, paddle and ball, as in the arcade cabinet.
+    pub fn arcade() -> Self {
+        Palette::new()
+            .with(0, ' ', [0, 0, 0])
+            .with(1, '#', [128, 128, 128])
+            .with(2, '+', [200, 80, 40])
+            .with(3, '=', [240, 240, 240])
+            .with(4, 'o', [250, 220, 40])
+    }
+
+    pub fn with(mut self, tile: C, glyph: char, color: [u8; 3]) -> Self {
+        self.tiles.insert(tile, (glyph, color));
+        self
+    }
+
+    pub fn glyph(&self, tile: C) -> char {
+        self.tiles.get(&tile).unwrap_or(&self.unknown).0
+    }
+
+    pub fn color(&self, tile: C) -> [u8; 3] {
+        self.tiles.get(&tile).unwrap_or(&self.unknown).1
+    }
+}
+
+/// A screen drawn by a program outputting (x, y, tile) triples. The triple
+/// (-1, 0, score) sets the score instead of drawing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Display {
+    tiles: BTreeMap<(C, C), C>,
+    pub score: Option<C>,
+    pending: Vec<C>,
+}
+
+impl Display {
+    pub fn new() -> Self {
+        Display::default()
+    }
+
+    /// Consume output values, keeping an incomplete triple for next time.
+    pub fn feed<I: IntoIterator<Item = C>>(&mut self, values: I) {
+        for value in values {
+            self.pending.push(value);
+            if let [x, y, tile] = self.pending[..] {
+                if (x, y) == (-1, 0) {
+                    self.score = Some(tile);
+                } else {
+                    self.tiles.insert((y, x), tile);
+                }
+                self.pending.clear();
+            }
+        }
+    }
+
+    /// Take everything `computer` has output so far.
+    pub fn update(&mut self, computer: &mut Computer) {
+        self.feed(computer.outputs.drain(..));
+    }
+
+    pub fn get(&self, x: C, y: C) -> Option<C> {
+        self.tiles.get(&(y, x)).cloned()
+    }
+
+    /// Positions holding `tile`, top to bottom then left to right.
+    pub fn find(&self, tile: C) -> impl Iterator<Item = (C, C)> + '_ {
+        self.tiles
+            .iter()
+            .filter(move |(_, &t)| t == tile)
+            .map(|(&(y, x), _)| (x, y))
+    }
+
+    pub fn count(&self, tile: C) -> usize {
+        self.find(tile).count()
+    }
+
+    /// Smallest and largest (x, y) drawn, or `None` if nothing has been.
+    pub fn bounds(&self) -> Option<((C, C), (C, C))> {
+        let xs = self.tiles.keys().map(|&(_, x)| x);
+        let (min_x, max_x) = (xs.clone().min()?, xs.max()?);
+        let min_y = self.tiles.keys().next()?.0;
+        let max_y = self.tiles.keys().next_back()?.0;
+        Some(((min_x, min_y), (max_x, max_y)))
+    }
+
+    /// Every row within the bounds as tile ids, `None` where nothing was
+    /// drawn.
+    fn rows(&self) -> Vec<Vec<Option<C>>> {
+        match self.bounds() {
+            Some(((min_x, min_y), (max_x, max_y))) => (min_y..=max_y)
+                .map(|y| (min_x..=max_x).map(|x| self.get(x, y)).collect())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// One line per row, each ending in a newline.
+    pub fn render(&self, palette: &Palette) -> String {
+        let mut text = String::new();
+        for row in self.rows() {
+            for tile in row {
+                text.push(match tile {
+                    Some(tile) => palette.glyph(tile),
+                    None => palette.unknown.0,
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Write a binary PPM image with each cell drawn `scale` pixels square.
+    pub fn write_ppm<W: Write>(&self, out: W, palette: &Palette, scale: usize) -> io::Result<()> {
+        let mut out = out;
+        let rows = self.rows();
+        let width = rows.first().map_or(0, |row| row.len());
+        write!(out, "P6\n{} {}\n255\n", width * scale, rows.len() * scale)?;
+        for row in rows {
+            let mut line = Vec::with_capacity(width * scale * 3);
+            for tile in row {
+                let color = match tile {
+                    Some(tile) => palette.color(tile),
+                    None => palette.unknown.1,
+                };
+                for _ in 0..scale {
+                    line.extend_from_slice(&color);
+                }
+            }
+            for _ in 0..scale {
+                out.write_all(&line)?;
+            }
+        }
+        out.flush()
+    }
+
+    pub fn save_ppm<P: AsRef<Path>>(
+        &self,
+        path: P,
+        palette: &Palette,
+        scale: usize,
+    ) -> io::Result<()> {
+        self.write_ppm(BufWriter::new(File::create(path)?), palette, scale)
+    }
+}
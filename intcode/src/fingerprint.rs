@@ -0,0 +1,47 @@
+use crate::{PagedMemory, C};
+
+/// What counts towards a computer's identity for `Hash`, `Eq` and
+/// `Computer::fingerprint`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Identity {
+    /// Memory, eip, relative base, pending inputs and outputs, and state.
+    #[default]
+    State,
+    /// `State` plus bookkeeping: the instruction counter and the last
+    /// instruction executed.
+    Full,
+}
+
+/// splitmix64's finalizer.
+pub(crate) fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Contribution of one memory cell to the memory hash. Zero cells contribute
+/// nothing, so padding doesn't change the hash.
+pub(crate) fn cell(addr: usize, value: C) -> u64 {
+    if value == 0 {
+        0
+    } else {
+        mix((addr as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ value as u64)
+    }
+}
+
+/// Order-independent hash of a whole memory image, kept up to date one
+/// cell at a time by `Computer::write`.
+pub(crate) fn memory(memory: &PagedMemory) -> u64 {
+    memory.iter().enumerate().fold(0, |hash, (addr, value)| {
+        hash.wrapping_add(cell(addr, value))
+    })
+}
+
+/// Fold `values` into `hash`, order mattering.
+pub(crate) fn sequence(hash: u64, values: &[C]) -> u64 {
+    values
+        .iter()
+        .fold(mix(hash ^ values.len() as u64), |hash, &value| {
+            mix(hash ^ value as u64)
+        })
+}
@@ -0,0 +1,176 @@
+use crate::{Computer, Policy, RoundRobin, State, C};
+use std::collections::VecDeque;
+
+pub type NodeId = usize;
+pub type EdgeId = usize;
+pub type TapId = usize;
+
+/// A queue of values flowing from one machine's output to another's input.
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub queue: VecDeque<C>,
+}
+
+/// Every value a machine has output since it was tapped.
+#[derive(Debug, Clone)]
+pub struct Tap {
+    pub node: NodeId,
+    pub values: Vec<C>,
+}
+
+/// Why `Graph::run` returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphState {
+    /// Every machine halted.
+    Halted,
+    /// Every machine that hasn't halted is waiting on an empty queue. More
+    /// input can be sent and the graph run again.
+    Deadlock,
+    /// A machine stopped on an instruction outside its profile.
+    Trapped(NodeId),
+    /// A machine stopped on something that isn't an instruction.
+    Fault(NodeId),
+}
+
+/// A directed graph of machines wired output to input.
+///
+/// Every output value is copied onto each of the machine's outgoing edges and
+/// taps. A machine with several incoming edges reads them in the order the
+/// edges were added, taking whatever each one holds when it is scheduled.
+/// Which machine runs next is up to a `Policy`; every policy is deterministic,
+/// so results are too.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    pub nodes: Vec<Computer>,
+    pub edges: Vec<Edge>,
+    pub taps: Vec<Tap>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Graph::default()
+    }
+
+    /// Add a machine that reads `inputs` before anything arriving on its
+    /// edges.
+    pub fn add_node(&mut self, computer: Computer, inputs: &[C]) -> NodeId {
+        let mut computer = computer;
+        computer.queue_inputs(inputs.iter().cloned());
+        self.nodes.push(computer);
+        self.nodes.len() - 1
+    }
+
+    pub fn connect(&mut self, from: NodeId, to: NodeId) -> EdgeId {
+        assert!(
+            from < self.nodes.len() && to < self.nodes.len(),
+            "No such node"
+        );
+        self.edges.push(Edge {
+            from,
+            to,
+            queue: VecDeque::new(),
+        });
+        self.edges.len() - 1
+    }
+
+    /// Connect each node to the next.
+    pub fn pipeline(&mut self, nodes: &[NodeId]) -> Vec<EdgeId> {
+        nodes.windows(2).map(|w| self.connect(w[0], w[1])).collect()
+    }
+
+    /// Connect each node to the next and the last back to the first.
+    pub fn ring(&mut self, nodes: &[NodeId]) -> Vec<EdgeId> {
+        let mut edges = self.pipeline(nodes);
+        if let (Some(&first), Some(&last)) = (nodes.first(), nodes.last()) {
+            edges.push(self.connect(last, first));
+        }
+        edges
+    }
+
+    /// Record everything `node` outputs from now on.
+    pub fn tap(&mut self, node: NodeId) -> TapId {
+        assert!(node < self.nodes.len(), "No such node");
+        self.taps.push(Tap {
+            node,
+            values: Vec::new(),
+        });
+        self.taps.len() - 1
+    }
+
+    pub fn tapped(&self, tap: TapId) -> &[C] {
+        &self.taps[tap].values
+    }
+
+    /// Feed `values` to `node` from outside the graph.
+    pub fn send(&mut self, node: NodeId, values: &[C]) {
+        self.nodes[node].queue_inputs(values.iter().cloned());
+    }
+
+    /// Whether `node` can execute anything without more input.
+    fn is_ready(&self, node: NodeId) -> bool {
+        match self.nodes[node].state {
+            State::Running => true,
+            State::AwaitingInput => {
+                !self.nodes[node].inputs.is_empty()
+                    || self
+                        .edges
+                        .iter()
+                        .any(|e| e.to == node && !e.queue.is_empty())
+            }
+            State::Halted | State::Trapped(_) | State::Fault(_) => false,
+        }
+    }
+
+    /// Run `node` until it outputs a value, blocks on input or stops, then
+    /// move whatever it wrote onto its edges.
+    fn run_node(&mut self, node: NodeId) {
+        let mut incoming = Vec::new();
+        for edge in self.edges.iter_mut().filter(|e| e.to == node) {
+            incoming.extend(edge.queue.drain(..));
+        }
+        let computer = &mut self.nodes[node];
+        computer.queue_inputs(incoming);
+        computer.run_to_event();
+
+        let outputs: Vec<C> = computer.outputs.drain(..).collect();
+        for edge in self.edges.iter_mut().filter(|e| e.from == node) {
+            edge.queue.extend(outputs.iter());
+        }
+        for tap in self.taps.iter_mut().filter(|t| t.node == node) {
+            tap.values.extend(outputs.iter());
+        }
+    }
+
+    /// Run machines in round-robin order until all of them halt or none can
+    /// make progress.
+    pub fn run(&mut self) -> GraphState {
+        self.run_with(&mut RoundRobin::default())
+    }
+
+    /// Run machines in the order `policy` picks until all of them halt or
+    /// none can make progress. Each pick runs one machine until its next
+    /// output, input wait or halt.
+    pub fn run_with<P: Policy + ?Sized>(&mut self, policy: &mut P) -> GraphState {
+        loop {
+            let ready: Vec<NodeId> = (0..self.nodes.len())
+                .filter(|&node| self.is_ready(node))
+                .collect();
+            if ready.is_empty() {
+                return if self.nodes.iter().all(|n| n.state == State::Halted) {
+                    GraphState::Halted
+                } else {
+                    GraphState::Deadlock
+                };
+            }
+            let node = policy.pick(&ready);
+            self.run_node(node);
+            match self.nodes[node].state {
+                State::Trapped(_) => return GraphState::Trapped(node),
+                State::Fault(_) => return GraphState::Fault(node),
+                _ => {}
+            }
+        }
+    }
+}
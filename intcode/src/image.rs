@@ -0,0 +1,317 @@
+use crate::{parse_program, Computer, Level, LoadError, Memory, C};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+/// First bytes of every image file.
+pub const MAGIC: [u8; 4] = *b"ICIM";
+/// The format version written by `Image::write`.
+pub const VERSION: u8 = 1;
+
+const HAS_NAME: u8 = 1;
+const HAS_SOURCE_HASH: u8 = 2;
+
+/// Why an image couldn't be read. Offsets are in bytes from the start.
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u8),
+    BadLevel(u8),
+    BadFlags(u8),
+    /// The data ended in the middle of something.
+    Truncated(usize),
+    /// A varint longer than 64 bits.
+    Overlong(usize),
+    /// The name isn't UTF-8.
+    BadName(usize),
+    /// Bytes left over after the last word.
+    TrailingBytes(usize),
+    EntryOutOfRange {
+        entry: usize,
+        len: usize,
+    },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "Can't read image: {}", e),
+            ImageError::BadMagic => write!(f, "Not an Intcode image"),
+            ImageError::UnsupportedVersion(v) => write!(f, "Unsupported image version {}", v),
+            ImageError::BadLevel(level) => write!(f, "Unknown level {}", level),
+            ImageError::BadFlags(flags) => write!(f, "Unknown flags {:#04x}", flags),
+            ImageError::Truncated(at) => write!(f, "Image ends early at byte {}", at),
+            ImageError::Overlong(at) => write!(f, "Varint too long at byte {}", at),
+            ImageError::BadName(at) => write!(f, "Name at byte {} isn't UTF-8", at),
+            ImageError::TrailingBytes(at) => write!(f, "Unexpected data at byte {}", at),
+            ImageError::EntryOutOfRange { entry, len } => {
+                write!(f, "Entry point {} is past the end of {} words", entry, len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(e: io::Error) -> Self {
+        ImageError::Io(e)
+    }
+}
+
+fn zigzag(value: C) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> C {
+    ((value >> 1) as C) ^ -((value & 1) as C)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// FNV-1a, for tying an image to the text it was built from.
+pub fn source_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Walks a byte slice, tracking the offset for errors.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Cursor<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], ImageError> {
+        if self.bytes.len() - self.at < n {
+            return Err(ImageError::Truncated(self.bytes.len()));
+        }
+        let taken = &self.bytes[self.at..self.at + n];
+        self.at += n;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, ImageError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, ImageError> {
+        let start = self.at;
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            if shift == 63 && byte > 1 {
+                return Err(ImageError::Overlong(start));
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ImageError::Overlong(start))
+    }
+
+    /// A varint used as a length or address.
+    fn size(&mut self) -> Result<usize, ImageError> {
+        let start = self.at;
+        let value = self.varint()?;
+        usize::try_from(value).map_err(|_| ImageError::Overlong(start))
+    }
+}
+
+/// A program plus what's needed to run it, in a compact binary form:
+///
+/// ```text
+/// magic "ICIM", version, level (0 = unspecified, else 2/5/9), flags,
+/// entry point, [name length, name], [source hash, 8 bytes little-endian],
+/// word count, words
+/// ```
+///
+/// Numbers are LEB128 varints, and words are zig-zag encoded first so small
+/// negative values stay short.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub level: Option<Level>,
+    pub entry: usize,
+    pub name: Option<String>,
+    pub source_hash: Option<u64>,
+    pub memory: Memory,
+}
+
+impl Image {
+    pub fn new(memory: Memory) -> Self {
+        Image {
+            level: None,
+            entry: 0,
+            name: None,
+            source_hash: None,
+            memory,
+        }
+    }
+
+    /// Parse a text program, recording the hash of `text`.
+    pub fn from_text(text: &str) -> Result<Self, LoadError> {
+        Ok(Image::new(parse_program(text)?).with_source_hash(source_hash(text)))
+    }
+
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    pub fn with_entry(mut self, entry: usize) -> Self {
+        self.entry = entry;
+        self
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn with_source_hash(mut self, hash: u64) -> Self {
+        self.source_hash = Some(hash);
+        self
+    }
+
+    /// The program as comma-separated text.
+    pub fn to_text(&self) -> String {
+        let words: Vec<String> = self.memory.iter().map(C::to_string).collect();
+        words.join(",")
+    }
+
+    /// A computer at the image's level, or day 9 if it has none, about to
+    /// execute the entry point.
+    pub fn computer(&self) -> Computer {
+        let mut computer =
+            Computer::with_level(self.memory.clone(), self.level.unwrap_or(Level::Day09));
+        computer.eip = self.entry;
+        computer
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        out.push(match self.level {
+            None => 0,
+            Some(Level::Day02) => 2,
+            Some(Level::Day05) => 5,
+            Some(Level::Day09) => 9,
+        });
+        let mut flags = 0;
+        if self.name.is_some() {
+            flags |= HAS_NAME;
+        }
+        if self.source_hash.is_some() {
+            flags |= HAS_SOURCE_HASH;
+        }
+        out.push(flags);
+        write_varint(&mut out, self.entry as u64);
+        if let Some(name) = self.name.as_ref() {
+            write_varint(&mut out, name.len() as u64);
+            out.extend_from_slice(name.as_bytes());
+        }
+        if let Some(hash) = self.source_hash {
+            out.extend_from_slice(&hash.to_le_bytes());
+        }
+        write_varint(&mut out, self.memory.len() as u64);
+        for &word in self.memory.iter() {
+            write_varint(&mut out, zigzag(word));
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+        let mut cursor = Cursor { bytes, at: 0 };
+        if cursor.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(ImageError::BadMagic);
+        }
+        let version = cursor.byte()?;
+        if version != VERSION {
+            return Err(ImageError::UnsupportedVersion(version));
+        }
+        let level = match cursor.byte()? {
+            0 => None,
+            2 => Some(Level::Day02),
+            5 => Some(Level::Day05),
+            9 => Some(Level::Day09),
+            other => return Err(ImageError::BadLevel(other)),
+        };
+        let flags = cursor.byte()?;
+        if flags & !(HAS_NAME | HAS_SOURCE_HASH) != 0 {
+            return Err(ImageError::BadFlags(flags));
+        }
+        let entry = cursor.size()?;
+        let name = if flags & HAS_NAME != 0 {
+            let len = cursor.size()?;
+            let at = cursor.at;
+            let name = String::from_utf8(cursor.take(len)?.to_vec())
+                .map_err(|_| ImageError::BadName(at))?;
+            Some(name)
+        } else {
+            None
+        };
+        let source_hash = if flags & HAS_SOURCE_HASH != 0 {
+            let mut hash = [0; 8];
+            hash.copy_from_slice(cursor.take(8)?);
+            Some(u64::from_le_bytes(hash))
+        } else {
+            None
+        };
+        let len = cursor.size()?;
+        // every word takes at least a byte, so don't trust `len` further
+        let mut memory = Vec::with_capacity(len.min(bytes.len() - cursor.at));
+        for _ in 0..len {
+            memory.push(unzigzag(cursor.varint()?));
+        }
+        if cursor.at != bytes.len() {
+            return Err(ImageError::TrailingBytes(cursor.at));
+        }
+        if entry > 0 && entry >= len {
+            return Err(ImageError::EntryOutOfRange { entry, len });
+        }
+        Ok(Image {
+            level,
+            entry,
+            name,
+            source_hash,
+            memory,
+        })
+    }
+
+    pub fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(&self.to_bytes())?;
+        out.flush()
+    }
+
+    pub fn read<R: Read>(mut input: R) -> Result<Self, ImageError> {
+        let mut bytes = Vec::new();
+        input.read_to_end(&mut bytes)?;
+        Image::from_bytes(&bytes)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        Image::from_bytes(&fs::read(path)?)
+    }
+}
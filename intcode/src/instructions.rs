@@ -0,0 +1,271 @@
+use crate::{Computer, Instruction, Opcode, State, Value, C};
+use std::fmt;
+use std::sync::Arc;
+use Role::*;
+
+const CODES: usize = 100;
+
+/// How an instruction uses one of its parameters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Role {
+    Read,
+    Write,
+}
+
+type Handler = Arc<dyn Fn(&mut Call) + Send + Sync>;
+
+/// Everything the VM needs to know about one opcode. Built-in opcodes are
+/// executed by the computer itself; registered ones carry a handler.
+#[derive(Clone)]
+pub struct Spec {
+    pub opcode: Opcode,
+    pub name: String,
+    pub params: Vec<Role>,
+    handler: Option<Handler>,
+}
+
+impl Spec {
+    /// Words taken by the instruction, including the opcode itself.
+    pub fn size(&self) -> usize {
+        1 + self.params.len()
+    }
+}
+
+impl fmt::Debug for Spec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}({}, {:?})", self.opcode, self.name, self.params)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DecodeError {
+    UnknownOpcode(C),
+    BadMode(C),
+    ImmediateWrite(usize),
+    Truncated,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnknownOpcode(code) => write!(f, "Bad instruction {}", code),
+            DecodeError::BadMode(mode) => write!(f, "Unexpected arg mode {}", mode),
+            DecodeError::ImmediateWrite(i) => {
+                write!(f, "Can't write a value in immediate mode (param {})", i)
+            }
+            DecodeError::Truncated => write!(f, "Instruction runs past end of memory"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Table of every opcode a computer understands, indexed by the two-digit
+/// code. Decoding, execution, disassembly and tracing all go through it.
+#[derive(Debug, Clone)]
+pub struct InstructionSet {
+    specs: Vec<Option<Spec>>,
+    max_size: usize,
+}
+
+impl Default for InstructionSet {
+    fn default() -> Self {
+        InstructionSet::standard()
+    }
+}
+
+impl InstructionSet {
+    /// No opcodes at all, not even exit.
+    pub fn empty() -> Self {
+        InstructionSet {
+            specs: vec![None; CODES],
+            max_size: 1,
+        }
+    }
+
+    /// The opcodes from days 2, 5 and 9.
+    pub fn standard() -> Self {
+        let mut set = InstructionSet::empty();
+        let builtins: [(C, &str, &[Role]); 10] = [
+            (1, "add", &[Read, Read, Write]),
+            (2, "mul", &[Read, Read, Write]),
+            (3, "in", &[Write]),
+            (4, "out", &[Read]),
+            (5, "jt", &[Read, Read]),
+            (6, "jf", &[Read, Read]),
+            (7, "lt", &[Read, Read, Write]),
+            (8, "eq", &[Read, Read, Write]),
+            (9, "arb", &[Read]),
+            (99, "halt", &[]),
+        ];
+        for (code, name, params) in builtins.iter() {
+            set.insert(Spec {
+                opcode: Opcode::new(*code),
+                name: name.to_string(),
+                params: params.to_vec(),
+                handler: None,
+            });
+        }
+        set
+    }
+
+    fn insert(&mut self, spec: Spec) {
+        let code = spec.opcode.code() as usize;
+        self.max_size = self.max_size.max(spec.size());
+        self.specs[code] = Some(spec);
+    }
+
+    /// Add an opcode. `handler` runs when it executes and is given read and
+    /// write access to the parameters through `Call`.
+    ///
+    /// Panics if `code` isn't a free two-digit code.
+    pub fn register<F>(&mut self, code: C, name: &str, params: &[Role], handler: F)
+    where
+        F: Fn(&mut Call) + Send + Sync + 'static,
+    {
+        assert!(
+            code > 0 && (code as usize) < CODES,
+            "Opcode {} is not a two-digit code",
+            code
+        );
+        assert!(
+            self.specs[code as usize].is_none(),
+            "Opcode {} is already registered",
+            code
+        );
+        self.insert(Spec {
+            opcode: Opcode::Custom(code),
+            name: name.to_string(),
+            params: params.to_vec(),
+            handler: Some(Arc::new(handler)),
+        });
+    }
+
+    pub fn spec(&self, code: C) -> Option<&Spec> {
+        if code < 0 || code as usize >= CODES {
+            return None;
+        }
+        self.specs[code as usize].as_ref()
+    }
+
+    /// Words taken by the longest instruction.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    pub fn specs(&self) -> impl Iterator<Item = &Spec> {
+        self.specs.iter().flatten()
+    }
+
+    pub(crate) fn handler(&self, code: C) -> Option<Handler> {
+        self.spec(code).and_then(|spec| spec.handler.clone())
+    }
+
+    /// Decode the instruction at `addr` without executing it.
+    pub fn decode(&self, memory: &[C], addr: usize) -> Result<Instruction, DecodeError> {
+        let mut instr = Instruction::empty();
+        self.decode_into(memory, addr, &mut instr)?;
+        Ok(instr)
+    }
+
+    /// `decode`, reusing the buffers of `instr` so a running computer
+    /// doesn't allocate on every step. On error `instr` is left half
+    /// written.
+    pub fn decode_into(
+        &self,
+        memory: &[C],
+        addr: usize,
+        instr: &mut Instruction,
+    ) -> Result<(), DecodeError> {
+        let word = *memory.get(addr).ok_or(DecodeError::Truncated)?;
+        let code = if word < 0 { word } else { word % 100 };
+        let spec = self.spec(code).ok_or(DecodeError::UnknownOpcode(code))?;
+        let raw = memory
+            .get(addr..addr + spec.size())
+            .ok_or(DecodeError::Truncated)?;
+        instr.opcode = spec.opcode;
+        instr.raw.clear();
+        instr.raw.extend_from_slice(raw);
+        instr.args.clear();
+        let mut modes = word / 100;
+        for (i, (&number, role)) in raw.iter().skip(1).zip(spec.params.iter()).enumerate() {
+            let value = Value::from(number, modes % 10)?;
+            if let (Write, Value::Immediate(_)) = (role, value) {
+                return Err(DecodeError::ImmediateWrite(i));
+            }
+            instr.args.push(value);
+            modes /= 10;
+        }
+        Ok(())
+    }
+
+    /// Render a decoded instruction, e.g. `add [9], 3, [rb-1]`.
+    pub fn format(&self, instr: &Instruction) -> String {
+        let name = match self.spec(instr.opcode.code()) {
+            Some(spec) => spec.name.clone(),
+            None => format!("{:?}", instr.opcode),
+        };
+        let args: Vec<String> = instr
+            .args
+            .iter()
+            .map(|arg| match arg {
+                Value::Pointer(addr) => format!("[{}]", addr),
+                Value::Immediate(value) => format!("{}", value),
+                Value::Relative(offset) if *offset < 0 => format!("[rb{}]", offset),
+                Value::Relative(offset) => format!("[rb+{}]", offset),
+            })
+            .collect();
+        if args.is_empty() {
+            name
+        } else {
+            format!("{} {}", name, args.join(", "))
+        }
+    }
+}
+
+/// The view a registered opcode's handler gets of the running computer.
+pub struct Call<'a> {
+    pub computer: &'a mut Computer,
+    pub instruction: &'a Instruction,
+    next_eip: usize,
+}
+
+impl<'a> Call<'a> {
+    pub(crate) fn new(
+        computer: &'a mut Computer,
+        instruction: &'a Instruction,
+        next_eip: usize,
+    ) -> Self {
+        Call {
+            computer,
+            instruction,
+            next_eip,
+        }
+    }
+
+    pub(crate) fn next_eip(&self) -> usize {
+        self.next_eip
+    }
+
+    /// Value of parameter `i`, honouring its addressing mode.
+    pub fn read(&mut self, i: usize) -> C {
+        self.computer.read(self.instruction.args[i])
+    }
+
+    /// Store `value` where parameter `i` points.
+    pub fn write(&mut self, i: usize, value: C) {
+        self.computer.write(self.instruction.args[i], value)
+    }
+
+    /// Continue at `eip` instead of the next instruction.
+    pub fn jump(&mut self, eip: usize) {
+        self.next_eip = eip;
+    }
+
+    /// Stay on this instruction and wait for input, like `Input` does when
+    /// there is none.
+    pub fn wait(&mut self) {
+        self.next_eip = self.computer.eip;
+        self.computer.state = State::AwaitingInput;
+    }
+}
@@ -0,0 +1,697 @@
+//! Intcode computer shared by every day that runs Intcode programs.
+//!
+//! Execution is instrumented with `tracing`: a `run` span around each
+//! `run_*` call, and `step`, `read`, `write`, `relative_base` and `trap`
+//! events carrying `eip`, `counter` and whichever of `opcode`, `address`,
+//! `value` and `mode` apply. All are at debug level. Without a subscriber
+//! they are forwarded to `log`. Each event's target is `intcode::` plus its
+//! name, e.g. `RUST_LOG=intcode::write=debug`. A disabled event costs one
+//! check of a cached flag; a `max_level_*` feature of `tracing` removes them
+//! entirely.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::{Arc, OnceLock};
+use tracing::{debug, debug_span};
+use Level::*;
+use Opcode::*;
+use State::*;
+use Value::*;
+
+mod arcade;
+mod ascii;
+mod devices;
+mod disasm;
+mod display;
+mod fingerprint;
+mod graph;
+mod image;
+mod instructions;
+mod load;
+mod maze;
+mod network;
+mod outputs;
+mod paging;
+mod phases;
+mod profile;
+mod rng;
+mod robot;
+mod scheduler;
+mod search;
+
+pub use arcade::{Arcade, BallTracker, Game, Joystick};
+pub use ascii::{Ascii, Text};
+pub use devices::{Bus, Console, Device, DeviceClone, Entropy, Framebuffer, Timer};
+pub use disasm::{disassemble, Line};
+pub use display::{Display, Palette};
+pub use fingerprint::Identity;
+pub use graph::{Edge, EdgeId, Graph, GraphState, NodeId, Tap, TapId};
+pub use image::{source_hash, Image, ImageError, MAGIC, VERSION};
+pub use instructions::{Call, DecodeError, InstructionSet, Role, Spec};
+pub use load::{load_program, parse_program, read_program, LoadError};
+pub use maze::{Maze, MazeError, Move, Position, Tile};
+pub use network::{Nat, Network, Packet, Round, Special, DEFAULT_STEP_BUDGET};
+pub use outputs::Outputs;
+pub use paging::{PagedMemory, PAGE_SIZE};
+pub use phases::{PhaseSearch, Ranked, SearchResult, TooManyConfigurations, Topology};
+pub use profile::{analyze, Mode, Profile, Requirements, Violation};
+pub use rng::Rng;
+pub use robot::{Direction, Robot, RobotState};
+pub use scheduler::{FifoReady, Policy, Random, RoundRobin};
+pub use search::{Found, Search, SearchOutcome, Strategy};
+
+pub type C = i64;
+pub type Memory = Vec<C>;
+
+const MEMORY_LENGTH: usize = 2048;
+
+/// Instruction-set levels in the order the puzzles introduced them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Add, multiply and exit, position mode only.
+    Day02,
+    /// Adds I/O, jumps, comparisons and immediate mode.
+    Day05,
+    /// Adds relative mode, `SetRelativeBase` and memory beyond the program.
+    Day09,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum Value {
+    Pointer(usize),
+    Immediate(C),
+    Relative(C),
+}
+
+impl Value {
+    /// The level that introduced this addressing mode.
+    pub fn level(&self) -> Level {
+        match self {
+            Pointer(_) => Day02,
+            Immediate(_) => Day05,
+            Relative(_) => Day09,
+        }
+    }
+
+    fn from(number: C, mode: C) -> Result<Self, DecodeError> {
+        match mode {
+            0 => Ok(Pointer(number as usize)),
+            1 => Ok(Immediate(number)),
+            2 => Ok(Relative(number)),
+            _ => Err(DecodeError::BadMode(mode)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Opcode {
+    Init,
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpTrue,
+    JumpFalse,
+    LessThan,
+    Equals,
+    SetRelativeBase,
+    Exit,
+    /// An opcode registered on an `InstructionSet`.
+    Custom(C),
+}
+
+impl Opcode {
+    pub fn new(code: C) -> Opcode {
+        match code {
+            1 => Add,
+            2 => Multiply,
+            3 => Input,
+            4 => Output,
+            5 => JumpTrue,
+            6 => JumpFalse,
+            7 => LessThan,
+            8 => Equals,
+            9 => SetRelativeBase,
+            99 => Exit,
+            _ => panic!("Bad instruction {:}", &code),
+        }
+    }
+
+    pub fn code(&self) -> C {
+        match self {
+            Init => 0,
+            Add => 1,
+            Multiply => 2,
+            Input => 3,
+            Output => 4,
+            JumpTrue => 5,
+            JumpFalse => 6,
+            LessThan => 7,
+            Equals => 8,
+            SetRelativeBase => 9,
+            Exit => 99,
+            Custom(code) => *code,
+        }
+    }
+
+    /// The level that introduced this opcode, or `None` for custom ones.
+    pub fn level(&self) -> Option<Level> {
+        match self {
+            Init | Add | Multiply | Exit => Some(Day02),
+            Input | Output | JumpTrue | JumpFalse | LessThan | Equals => Some(Day05),
+            SetRelativeBase => Some(Day09),
+            Custom(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub args: Vec<Value>,
+    pub raw: Memory,
+}
+
+impl Instruction {
+    /// Stands in for the last instruction before anything has executed.
+    pub fn empty() -> Self {
+        Instruction {
+            opcode: Init,
+            args: Vec::new(),
+            raw: Vec::new(),
+        }
+    }
+}
+
+/// Spare buffers for `InstructionSet::decode_into`, swapped with `last`
+/// after each step. Clones start empty rather than copying stale buffers.
+struct Scratch(Instruction);
+
+impl Clone for Scratch {
+    fn clone(&self) -> Self {
+        Scratch(Instruction::empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum State {
+    Running,
+    AwaitingInput,
+    Halted,
+    /// Stopped before executing an instruction its profile doesn't allow.
+    Trapped(Violation),
+    /// Stopped at something that doesn't decode as an instruction.
+    Fault(DecodeError),
+}
+
+/// Why an in-place run returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// The last value written to `outputs`.
+    Output(C),
+    /// Waiting for input and none is queued.
+    AwaitingInput,
+    Halted,
+    Trapped(Violation),
+    Fault(DecodeError),
+    /// Still running when the step budget ran out.
+    StepLimit,
+}
+
+impl Event {
+    /// The value, if this is an output, e.g. for `map_while`.
+    pub fn output(self) -> Option<C> {
+        match self {
+            Event::Output(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Computer {
+    memory: PagedMemory,
+    pub eip: usize,
+    pub counter: usize,
+    pub relative_base: C,
+    pub profile: Profile,
+    pub instructions: Arc<InstructionSet>,
+    pub state: State,
+    pub last: Instruction,
+    pub inputs: Vec<C>,
+    pub outputs: Vec<C>,
+    pub bus: Bus,
+    pub identity: Identity,
+    /// Kept up to date by every write, which is why `memory` is private.
+    memory_hash: u64,
+    scratch: Scratch,
+}
+
+impl fmt::Debug for Computer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[ {:04} ] {:?}::{{ eip={} rel={} in={:?} out={:?} }}",
+            self.counter, self.state, self.eip, self.relative_base, self.inputs, self.outputs,
+        )
+    }
+}
+
+/// Computers with different identities are never equal. Only the machine
+/// itself is compared: `bus`, `profile` and `instructions` are ignored, so
+/// machines wired or configured differently can still be equal.
+impl PartialEq for Computer {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity == other.identity
+            && self.memory_hash == other.memory_hash
+            && self.eip == other.eip
+            && self.relative_base == other.relative_base
+            && self.state == other.state
+            && self.inputs == other.inputs
+            && self.outputs == other.outputs
+            && self.memory == other.memory
+            && (self.identity == Identity::State
+                || (self.counter == other.counter && self.last.raw == other.last.raw))
+    }
+}
+
+impl Eq for Computer {}
+
+/// Hashes `fingerprint`, so it ignores the same fields as `PartialEq`.
+impl Hash for Computer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.fingerprint());
+    }
+}
+
+impl Computer {
+    pub fn new(memory: Memory) -> Self {
+        Computer::with_level(memory, Day09)
+    }
+
+    pub fn with_level(memory: Memory, level: Level) -> Self {
+        Computer::with_profile(memory, level.into())
+    }
+
+    /// Instructions outside `profile` trap instead of executing.
+    pub fn with_profile(memory: Memory, profile: Profile) -> Self {
+        static STANDARD: OnceLock<Arc<InstructionSet>> = OnceLock::new();
+        let instructions = STANDARD.get_or_init(|| Arc::new(InstructionSet::standard()));
+        Computer::with_instructions(memory, profile, instructions.clone())
+    }
+
+    /// Run with extra opcodes registered on top of the standard set. The
+    /// profile must allow them too, e.g. `Profile::all()`.
+    ///
+    /// Only profiles with extended memory may address past the end of the
+    /// program, so other profiles keep the image at its original length.
+    pub fn with_instructions(
+        memory: Memory,
+        profile: Profile,
+        instructions: Arc<InstructionSet>,
+    ) -> Self {
+        let mut memory = PagedMemory::from(memory);
+        if profile.extended_memory {
+            memory.pad(MEMORY_LENGTH);
+        }
+        Computer {
+            eip: 0,
+            counter: 0,
+            relative_base: 0,
+            profile,
+            instructions,
+            state: Running,
+            last: Instruction::empty(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            bus: Bus::default(),
+            identity: Identity::default(),
+            memory_hash: fingerprint::memory(&memory),
+            memory,
+            scratch: Scratch(Instruction::empty()),
+        }
+    }
+
+    /// Queue `values` to be read after any inputs already waiting.
+    pub fn queue_inputs<I: IntoIterator<Item = C>>(&mut self, values: I) {
+        // inputs are popped from the end, so the queue is stored reversed
+        let mut inputs: Vec<C> = values.into_iter().collect();
+        inputs.reverse();
+        inputs.append(&mut self.inputs);
+        self.inputs = inputs;
+    }
+
+    /// Map `device` over `len` addresses starting at `start`. Reads and
+    /// writes there go to the device instead of memory.
+    pub fn attach<D: Device + 'static>(&mut self, start: usize, len: usize, device: D) {
+        self.bus.map(start, len, device);
+    }
+
+    fn address(&self, location: Value) -> usize {
+        match location {
+            Immediate(_) => panic!("Can't address a value in immediate mode"),
+            Pointer(addr) => addr,
+            Relative(offset) => (self.relative_base + offset) as usize,
+        }
+    }
+
+    fn read(&mut self, location: Value) -> C {
+        if let Immediate(value) = location {
+            debug!(
+                name: "read",
+                target: "intcode::read",
+                eip = self.eip,
+                counter = self.counter,
+                value,
+                mode = ?Mode::Immediate
+            );
+            return value;
+        }
+        let addr = self.address(location);
+        let value = match self.bus.read(addr) {
+            Some(value) => value,
+            None => self.memory[addr],
+        };
+        debug!(
+            name: "read",
+            target: "intcode::read",
+            eip = self.eip,
+            counter = self.counter,
+            address = addr,
+            value,
+            mode = ?Mode::of(&location)
+        );
+        value
+    }
+
+    fn write(&mut self, location: Value, value: C) {
+        if let Immediate(_) = location {
+            panic!("Can't write a value in immediate mode");
+        }
+        let addr = self.address(location);
+        debug!(
+            name: "write",
+            target: "intcode::write",
+            eip = self.eip,
+            counter = self.counter,
+            address = addr,
+            value,
+            mode = ?Mode::of(&location)
+        );
+        if !self.bus.write(addr, value) {
+            self.set(addr, value);
+        }
+    }
+
+    pub fn memory(&self) -> &PagedMemory {
+        &self.memory
+    }
+
+    /// Store `value` in memory, bypassing devices, and keep the fingerprint
+    /// current.
+    pub fn set(&mut self, addr: usize, value: C) {
+        let old = self.memory.set(addr, value);
+        self.memory_hash = self
+            .memory_hash
+            .wrapping_sub(fingerprint::cell(addr, old))
+            .wrapping_add(fingerprint::cell(addr, value));
+    }
+
+    /// A stable hash of everything `identity` covers. Memory is hashed
+    /// incrementally, so this costs the same whatever its size.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = fingerprint::sequence(self.machine_fingerprint(), &self.outputs);
+        if self.identity == Identity::Full {
+            hash = fingerprint::mix(hash ^ self.counter as u64);
+            hash = fingerprint::sequence(hash, &self.last.raw);
+        }
+        hash
+    }
+
+    /// Like `fingerprint`, but only memory, eip, relative base, pending
+    /// inputs and state: two machines that will behave the same from here on
+    /// match whatever they output to get there.
+    pub fn machine_fingerprint(&self) -> u64 {
+        let state = match self.state {
+            Running => 0,
+            AwaitingInput => 1,
+            Halted => 2,
+            Trapped(Violation::Opcode(code)) => fingerprint::mix(3 ^ code as u64),
+            Trapped(Violation::Mode(mode)) => fingerprint::mix(4 ^ mode as u64),
+            Fault(DecodeError::UnknownOpcode(code)) => fingerprint::mix(5 ^ code as u64),
+            Fault(DecodeError::BadMode(mode)) => fingerprint::mix(6 ^ mode as u64),
+            Fault(DecodeError::ImmediateWrite(i)) => fingerprint::mix(7 ^ i as u64),
+            Fault(DecodeError::Truncated) => 8,
+        };
+        let mut hash = fingerprint::mix(self.memory_hash);
+        for &word in [self.eip as u64, self.relative_base as u64, state].iter() {
+            hash = fingerprint::mix(hash ^ word);
+        }
+        fingerprint::sequence(hash, &self.inputs)
+    }
+
+    /// Decode the instruction at `eip` into the scratch buffers.
+    fn extract_instruction(&mut self) -> Result<Instruction, DecodeError> {
+        let mut instr = mem::replace(&mut self.scratch.0, Instruction::empty());
+        let window = self.memory.window(self.eip, self.instructions.max_size());
+        let decoded = self.instructions.decode_into(&window, 0, &mut instr);
+        match decoded {
+            Ok(()) => Ok(instr),
+            Err(e) => {
+                self.scratch.0 = instr;
+                Err(e)
+            }
+        }
+    }
+
+    /// Stop at an instruction that can't be executed.
+    fn fault(&mut self, error: DecodeError) {
+        debug!(
+            name: "trap",
+            target: "intcode::trap",
+            eip = self.eip,
+            counter = self.counter,
+            fault = %error
+        );
+        self.state = Fault(error);
+    }
+
+    fn apply(&mut self, instr: Instruction) {
+        let mut next_eip = self.eip + instr.raw.len();
+        match instr.opcode {
+            Init => unimplemented!(),
+            Add => {
+                let result = self.read(instr.args[0]) + self.read(instr.args[1]);
+                self.write(instr.args[2], result);
+            }
+            Multiply => {
+                let result = self.read(instr.args[0]) * self.read(instr.args[1]);
+                self.write(instr.args[2], result);
+            }
+            Exit => {
+                self.state = Halted;
+            }
+            Input => {
+                if let Some(input) = self.inputs.pop() {
+                    self.write(instr.args[0], input);
+                    self.state = State::Running;
+                } else {
+                    // do nothing and wait
+                    next_eip = self.eip;
+                    self.state = State::AwaitingInput;
+                }
+            }
+            JumpTrue => {
+                let value = self.read(instr.args[0]);
+                if value != 0 {
+                    let jump_to = self.read(instr.args[1]);
+                    next_eip = jump_to as usize;
+                }
+            }
+            JumpFalse => {
+                let value = self.read(instr.args[0]);
+                if value == 0 {
+                    let jump_to = self.read(instr.args[1]);
+                    next_eip = jump_to as usize;
+                }
+            }
+            LessThan => {
+                let less_than = self.read(instr.args[0]) < self.read(instr.args[1]);
+                self.write(
+                    instr.args[2],
+                    match less_than {
+                        true => 1,
+                        false => 0,
+                    },
+                );
+            }
+            Equals => {
+                let equal = self.read(instr.args[0]) == self.read(instr.args[1]);
+                self.write(
+                    instr.args[2],
+                    match equal {
+                        true => 1,
+                        false => 0,
+                    },
+                );
+            }
+            Output => {
+                let value = self.read(instr.args[0]);
+                self.outputs.push(value);
+            }
+            SetRelativeBase => {
+                let offset = self.read(instr.args[0]);
+                debug!(
+                    name: "relative_base",
+                    target: "intcode::relative_base",
+                    eip = self.eip,
+                    counter = self.counter,
+                    value = self.relative_base + offset,
+                    offset
+                );
+                self.relative_base += offset;
+            }
+            Custom(code) => {
+                let handler = match self.instructions.handler(code) {
+                    Some(handler) => handler,
+                    None => {
+                        self.fault(DecodeError::UnknownOpcode(code));
+                        self.scratch.0 = instr;
+                        return;
+                    }
+                };
+                self.state = State::Running;
+                let mut call = Call::new(self, &instr, next_eip);
+                handler(&mut call);
+                next_eip = call.next_eip();
+            }
+        }
+        self.eip = next_eip;
+        self.scratch.0 = mem::replace(&mut self.last, instr);
+        self.counter += 1;
+        if !self.bus.is_empty() {
+            self.bus.tick();
+        }
+    }
+
+    pub fn step_mut(&mut self) {
+        let instr = match self.extract_instruction() {
+            Ok(instr) => instr,
+            Err(e) => return self.fault(e),
+        };
+        debug!(
+            name: "step",
+            target: "intcode::step",
+            eip = self.eip,
+            counter = self.counter,
+            opcode = instr.opcode.code(),
+            instruction = %self.instructions.format(&instr)
+        );
+        if let Err(violation) = self.profile.check(&instr) {
+            debug!(
+                name: "trap",
+                target: "intcode::trap",
+                eip = self.eip,
+                counter = self.counter,
+                opcode = instr.opcode.code(),
+                violation = %violation
+            );
+            self.state = Trapped(violation);
+            self.scratch.0 = instr;
+            return;
+        }
+        self.apply(instr);
+    }
+
+    /// Pick up where a machine waiting for input left off, if there is
+    /// input for it now. The `run_*` methods do this themselves.
+    pub fn resume(&mut self) {
+        if self.state == AwaitingInput && !self.inputs.is_empty() {
+            self.state = Running;
+        }
+    }
+
+    fn span(&self, method: &'static str) -> tracing::Span {
+        debug_span!(target: "intcode::run", "run", method, eip = self.eip, counter = self.counter)
+    }
+
+    fn event(&self) -> Event {
+        match self.state {
+            Running => Event::StepLimit,
+            AwaitingInput => Event::AwaitingInput,
+            Halted => Event::Halted,
+            Trapped(violation) => Event::Trapped(violation),
+            Fault(error) => Event::Fault(error),
+        }
+    }
+
+    /// Run until the next instruction that outputs, or until the machine
+    /// stops. The output is also left in `outputs`.
+    pub fn run_to_event(&mut self) -> Event {
+        let _span = self.span("run_to_event").entered();
+        self.resume();
+        while self.state == Running {
+            let written = self.outputs.len();
+            self.step_mut();
+            if self.outputs.len() > written {
+                return Event::Output(self.outputs[self.outputs.len() - 1]);
+            }
+        }
+        self.event()
+    }
+
+    /// Execute at most `steps` instructions.
+    pub fn run_steps(&mut self, steps: usize) -> Event {
+        let _span = self.span("run_steps").entered();
+        self.resume();
+        for _ in 0..steps {
+            if self.state != Running {
+                break;
+            }
+            self.step_mut();
+        }
+        self.event()
+    }
+
+    /// Run until the machine halts, traps or waits for input it doesn't
+    /// have.
+    pub fn run_to_halt(&mut self) -> Event {
+        let _span = self.span("run_to_halt").entered();
+        self.resume();
+        while self.state == Running {
+            self.step_mut();
+        }
+        self.event()
+    }
+
+    /// Queue `inputs` and iterate over the outputs as the machine produces
+    /// them. Outputs are taken out of `outputs` as they are yielded.
+    pub fn outputs_iter<I: IntoIterator<Item = C>>(&mut self, inputs: I) -> Outputs<'_> {
+        self.queue_inputs(inputs);
+        Outputs::new(self)
+    }
+
+    /// A copy run until `counter` passes `limit` or it stops.
+    pub fn run_until(&self, limit: Option<usize>) -> Self {
+        let mut result = self.clone();
+        match limit {
+            Some(limit) => {
+                let steps = limit.saturating_add(1).saturating_sub(result.counter);
+                result.run_steps(steps);
+            }
+            None => {
+                result.run_to_halt();
+            }
+        }
+        result
+    }
+
+    /// A copy run until it stops; see `run_to_halt`.
+    pub fn run(&self) -> Self {
+        self.run_until(None)
+    }
+}
+
+#[cfg(test)]
+mod tests;
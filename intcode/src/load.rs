@@ -0,0 +1,145 @@
+use crate::{Memory, C};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Why a program couldn't be loaded. Lines and columns count from 1.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// A token that isn't a number that fits in a `C`.
+    BadValue {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    /// Brackets out of place in a JSON-style array.
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// No values at all.
+    Empty,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "Can't read program: {}", e),
+            LoadError::BadValue {
+                line,
+                column,
+                token,
+            } => write!(f, "{}:{}: bad value {:?}", line, column, token),
+            LoadError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}: {}", line, column, message),
+            LoadError::Empty => write!(f, "Program is empty"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+fn syntax<T>(line: usize, column: usize, message: &str) -> Result<T, LoadError> {
+    Err(LoadError::Syntax {
+        line,
+        column,
+        message: message.to_string(),
+    })
+}
+
+/// Parse a program written as comma-separated values, one value per line,
+/// or a JSON array. Commas and whitespace can be mixed freely, so trailing
+/// commas and blank lines are fine, and `#` starts a comment running to the
+/// end of the line.
+pub fn parse_program(text: &str) -> Result<Memory, LoadError> {
+    let mut memory = Vec::new();
+    // None before the array opens, Some(true) inside it, Some(false) after
+    let mut array: Option<bool> = None;
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let code = match raw.find('#') {
+            Some(comment) => &raw[..comment],
+            None => raw,
+        };
+        let mut chars = code.char_indices().peekable();
+        // counted as we go, since rescanning the line per token is quadratic
+        let mut next_column = 1;
+        while let Some((start, c)) = chars.next() {
+            let column = next_column;
+            next_column += 1;
+            match c {
+                ',' => {}
+                c if c.is_whitespace() => {}
+                '[' if array.is_none() && memory.is_empty() => array = Some(true),
+                '[' => return syntax(line, column, "unexpected '['"),
+                ']' if array == Some(true) => array = Some(false),
+                ']' => return syntax(line, column, "unexpected ']'"),
+                _ if array == Some(false) => {
+                    return syntax(line, column, "values after the closing ']'")
+                }
+                _ => {
+                    let mut end = start + c.len_utf8();
+                    while let Some(&(next, c)) = chars.peek() {
+                        if c == ',' || c == '[' || c == ']' || c.is_whitespace() {
+                            break;
+                        }
+                        end = next + c.len_utf8();
+                        next_column += 1;
+                        chars.next();
+                    }
+                    let token = &code[start..end];
+                    match token.parse::<C>() {
+                        Ok(value) => memory.push(value),
+                        Err(_) => {
+                            return Err(LoadError::BadValue {
+                                line,
+                                column,
+                                token: token.to_string(),
+                            })
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if array == Some(true) {
+        let line = text.lines().count().max(1);
+        let column = text.lines().last().map_or(0, |l| l.chars().count()) + 1;
+        return syntax(line, column, "missing ']'");
+    }
+    if memory.is_empty() {
+        return Err(LoadError::Empty);
+    }
+    Ok(memory)
+}
+
+/// Read everything from `reader` and parse it with `parse_program`.
+pub fn read_program<R: Read>(mut reader: R) -> Result<Memory, LoadError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    parse_program(&text)
+}
+
+/// Load a program file with `parse_program`.
+pub fn load_program<P: AsRef<Path>>(path: P) -> Result<Memory, LoadError> {
+    read_program(File::open(path)?)
+}
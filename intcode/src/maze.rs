@@ -0,0 +1,223 @@
+use crate::rng::Rng;
+use crate::{Computer, Event, C};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+pub type Position = (C, C);
+
+/// A droid command, by its code in the movement protocol.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Move {
+    North = 1,
+    South = 2,
+    West = 3,
+    East = 4,
+}
+
+impl Move {
+    pub const ALL: [Move; 4] = [Move::North, Move::South, Move::West, Move::East];
+
+    /// (dx, dy) of the move, with y growing downwards.
+    pub fn delta(self) -> (C, C) {
+        match self {
+            Move::North => (0, -1),
+            Move::South => (0, 1),
+            Move::West => (-1, 0),
+            Move::East => (1, 0),
+        }
+    }
+
+    pub fn apply(self, (x, y): Position) -> Position {
+        let (dx, dy) = self.delta();
+        (x + dx, y + dy)
+    }
+}
+
+/// What the droid found in a cell, by its status reply.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Tile {
+    Wall,
+    Open,
+    Target,
+}
+
+impl Tile {
+    fn from_status(status: C) -> Option<Tile> {
+        match status {
+            0 => Some(Tile::Wall),
+            1 => Some(Tile::Open),
+            2 => Some(Tile::Target),
+            _ => None,
+        }
+    }
+}
+
+/// Why `Maze::explore` gave up. `at` is the cell the droid was asked to
+/// move into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MazeError {
+    /// A reply other than 0, 1 or 2.
+    BadStatus { at: Position, status: C },
+    /// The droid stopped without replying to a move.
+    Silent { at: Position, event: Event },
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MazeError::BadStatus { at, status } => {
+                write!(f, "Bad droid status {} moving to {:?}", status, at)
+            }
+            MazeError::Silent { at, event } => {
+                write!(f, "Droid stopped answering moving to {:?}: {:?}", at, event)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MazeError {}
+
+/// A fully explored maze. The droid starts at the origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Maze {
+    pub tiles: BTreeMap<Position, Tile>,
+    pub target: Option<Position>,
+    /// Open cells in the order they were reached.
+    pub order: Vec<Position>,
+}
+
+impl Maze {
+    /// Map everything reachable by a droid that takes a `Move` code and
+    /// replies 0 (hit a wall), 1 (moved) or 2 (moved onto the target).
+    ///
+    /// The droid is cloned at every cell reached, so each probe is a single
+    /// move from a known state and nothing has to be walked back. `seed`
+    /// shuffles the order moves are tried in.
+    pub fn explore(droid: &Computer, seed: u64) -> Result<Maze, MazeError> {
+        let mut rng = Rng::new(seed);
+        let mut maze = Maze {
+            tiles: BTreeMap::new(),
+            target: None,
+            order: vec![(0, 0)],
+        };
+        maze.tiles.insert((0, 0), Tile::Open);
+        let mut frontier = VecDeque::new();
+        frontier.push_back(((0, 0), droid.clone()));
+        while let Some((position, droid)) = frontier.pop_front() {
+            let mut moves = Move::ALL;
+            rng.shuffle(&mut moves);
+            for &step in moves.iter() {
+                let next = step.apply(position);
+                if maze.tiles.contains_key(&next) {
+                    continue;
+                }
+                let mut probe = droid.clone();
+                let status = match probe_move(&mut probe, step) {
+                    Event::Output(status) => status,
+                    event => return Err(MazeError::Silent { at: next, event }),
+                };
+                let tile =
+                    Tile::from_status(status).ok_or(MazeError::BadStatus { at: next, status })?;
+                maze.tiles.insert(next, tile);
+                if tile == Tile::Wall {
+                    continue;
+                }
+                if tile == Tile::Target {
+                    maze.target = Some(next);
+                }
+                maze.order.push(next);
+                frontier.push_back((next, probe));
+            }
+        }
+        Ok(maze)
+    }
+
+    pub fn is_open(&self, position: Position) -> bool {
+        matches!(
+            self.tiles.get(&position),
+            Some(Tile::Open) | Some(Tile::Target)
+        )
+    }
+
+    /// Steps from `from` to every open cell reachable from it.
+    pub fn distances(&self, from: Position) -> BTreeMap<Position, usize> {
+        let mut distances = BTreeMap::new();
+        if !self.is_open(from) {
+            return distances;
+        }
+        distances.insert(from, 0);
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[&position];
+            for step in Move::ALL.iter() {
+                let next = step.apply(position);
+                if self.is_open(next) && !distances.contains_key(&next) {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// The moves of a shortest route from `from` to `to`.
+    pub fn shortest_path(&self, from: Position, to: Position) -> Option<Vec<Move>> {
+        // walk back downhill from `to`
+        let distances = self.distances(from);
+        let mut distance = *distances.get(&to)?;
+        let mut position = to;
+        let mut path = Vec::with_capacity(distance);
+        while distance > 0 {
+            let (step, previous) = Move::ALL
+                .iter()
+                .map(|&step| {
+                    let (dx, dy) = step.delta();
+                    (step, (position.0 - dx, position.1 - dy))
+                })
+                .find(|(_, previous)| distances.get(previous) == Some(&(distance - 1)))
+                .unwrap();
+            path.push(step);
+            position = previous;
+            distance -= 1;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The shortest route from the start to the target.
+    pub fn path_to_target(&self) -> Option<Vec<Move>> {
+        self.shortest_path((0, 0), self.target?)
+    }
+
+    /// `#` walls, `.` open, `D` the start, `O` the target and spaces for
+    /// cells never probed.
+    pub fn render(&self) -> String {
+        let min_x = self.tiles.keys().map(|p| p.0).min().unwrap_or(0);
+        let max_x = self.tiles.keys().map(|p| p.0).max().unwrap_or(0);
+        let min_y = self.tiles.keys().map(|p| p.1).min().unwrap_or(0);
+        let max_y = self.tiles.keys().map(|p| p.1).max().unwrap_or(0);
+        let mut text = String::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                text.push(match self.tiles.get(&(x, y)) {
+                    _ if (x, y) == (0, 0) => 'D',
+                    Some(Tile::Wall) => '#',
+                    Some(Tile::Open) => '.',
+                    Some(Tile::Target) => 'O',
+                    None => ' ',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Send one move and wait for the status reply, an `Event::Output`.
+fn probe_move(droid: &mut Computer, step: Move) -> Event {
+    droid.queue_inputs(Some(step as C));
+    let event = droid.run_to_event();
+    droid.outputs.clear();
+    event
+}
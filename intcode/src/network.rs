@@ -0,0 +1,267 @@
+use crate::{Computer, Event, Memory, State, C};
+use std::collections::{BTreeMap, VecDeque};
+use tracing::debug;
+
+/// Sent when a machine polls an empty queue.
+const EMPTY: C = -1;
+
+/// Instructions a machine may run per round unless `with_step_budget` says
+/// otherwise. Real NICs wait for input long before this.
+pub const DEFAULT_STEP_BUDGET: usize = 1_000_000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub from: C,
+    pub to: C,
+    pub x: C,
+    pub y: C,
+}
+
+/// Something listening on an address that isn't one of the machines.
+pub trait Special {
+    /// A packet was sent to this handler's address. Any packets returned are
+    /// routed straight away.
+    fn receive(&mut self, packet: Packet) -> Vec<Packet>;
+
+    /// The network has gone idle. Any packets returned are routed straight
+    /// away.
+    fn idle(&mut self) -> Vec<Packet> {
+        Vec::new()
+    }
+}
+
+/// Remembers the last packet it received and sends it to machine 0 whenever
+/// the network goes idle.
+#[derive(Debug, Clone)]
+pub struct Nat {
+    pub address: C,
+    pub last: Option<Packet>,
+}
+
+impl Nat {
+    pub fn new(address: C) -> Self {
+        Nat {
+            address,
+            last: None,
+        }
+    }
+}
+
+impl Special for Nat {
+    fn receive(&mut self, packet: Packet) -> Vec<Packet> {
+        self.last = Some(packet);
+        Vec::new()
+    }
+
+    fn idle(&mut self) -> Vec<Packet> {
+        match self.last {
+            Some(last) => vec![Packet {
+                from: self.address,
+                to: 0,
+                ..last
+            }],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// What happened during one call to `Network::step`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Round {
+    /// Every packet sent by a machine, in the order they were sent.
+    pub sent: Vec<Packet>,
+    /// Packets sent by special handlers because the network went idle.
+    pub woken: Vec<Packet>,
+    /// Machines still running when their step budget ran out. They carry on
+    /// from there next round.
+    pub over_budget: Vec<C>,
+    /// No machine had anything queued, none of them sent anything and none
+    /// ran out of budget.
+    pub idle: bool,
+}
+
+/// Machines addressed 0..N exchanging (address, X, Y) packets.
+///
+/// Each round runs every machine in address order until it waits for input
+/// or uses up `step_budget`, feeding it its whole queue, or -1 if the queue
+/// is empty. Packets are routed as soon as they are sent, so the schedule is
+/// fully deterministic.
+pub struct Network {
+    pub machines: Vec<Computer>,
+    pub queues: Vec<VecDeque<C>>,
+    specials: BTreeMap<C, Box<dyn Special>>,
+    /// Consecutive idle rounds before special handlers are told.
+    pub idle_after: usize,
+    /// Instructions each machine may run per round.
+    pub step_budget: usize,
+    idle_rounds: usize,
+    /// Packets sent to an address nobody is listening on.
+    pub dropped: Vec<Packet>,
+    pub rounds: usize,
+}
+
+impl Network {
+    /// Boot `size` copies of `program`, each given its address as its first
+    /// input.
+    pub fn new(program: &Memory, size: usize) -> Self {
+        Network::from_machines((0..size).map(|_| Computer::new(program.clone())).collect())
+    }
+
+    /// Network `machines`, addressed in order. Each reads its address
+    /// before any input already queued on it.
+    pub fn from_machines(machines: Vec<Computer>) -> Self {
+        let machines: Vec<Computer> = machines
+            .into_iter()
+            .enumerate()
+            .map(|(address, mut machine)| {
+                // inputs are popped from the end
+                machine.inputs.push(address as C);
+                machine
+            })
+            .collect();
+        Network {
+            queues: vec![VecDeque::new(); machines.len()],
+            machines,
+            specials: BTreeMap::new(),
+            idle_after: 1,
+            step_budget: DEFAULT_STEP_BUDGET,
+            idle_rounds: 0,
+            dropped: Vec::new(),
+            rounds: 0,
+        }
+    }
+
+    /// Listen on `address` with `handler`.
+    ///
+    /// Panics if `address` belongs to a machine.
+    pub fn with_special<S: Special + 'static>(mut self, address: C, handler: S) -> Self {
+        assert!(
+            !self.is_machine(address),
+            "Address {} belongs to a machine",
+            address
+        );
+        self.specials.insert(address, Box::new(handler));
+        self
+    }
+
+    pub fn with_idle_after(mut self, rounds: usize) -> Self {
+        self.idle_after = rounds;
+        self
+    }
+
+    pub fn with_step_budget(mut self, steps: usize) -> Self {
+        self.step_budget = steps;
+        self
+    }
+
+    fn is_machine(&self, address: C) -> bool {
+        0 <= address && (address as usize) < self.machines.len()
+    }
+
+    /// Deliver `packet`, returning any packets sent in reply.
+    pub fn send(&mut self, packet: Packet) -> Vec<Packet> {
+        debug!(
+            name: "packet",
+            target: "intcode::packet",
+            from = packet.from,
+            to = packet.to,
+            x = packet.x,
+            y = packet.y
+        );
+        if self.is_machine(packet.to) {
+            let queue = &mut self.queues[packet.to as usize];
+            queue.push_back(packet.x);
+            queue.push_back(packet.y);
+            Vec::new()
+        } else if let Some(handler) = self.specials.get_mut(&packet.to) {
+            handler.receive(packet)
+        } else {
+            self.dropped.push(packet);
+            Vec::new()
+        }
+    }
+
+    fn route(&mut self, packets: Vec<Packet>) {
+        let mut pending: VecDeque<Packet> = packets.into();
+        while let Some(packet) = pending.pop_front() {
+            pending.extend(self.send(packet));
+        }
+    }
+
+    /// Run one round of every machine.
+    pub fn step(&mut self) -> Round {
+        let mut round = Round::default();
+        let mut quiet = true;
+        for address in 0..self.machines.len() {
+            let machine = &mut self.machines[address];
+            if stopped(machine) {
+                continue;
+            }
+            let queue = &mut self.queues[address];
+            if queue.is_empty() {
+                queue.push_back(EMPTY);
+            } else {
+                quiet = false;
+            }
+            machine.queue_inputs(queue.drain(..));
+            if machine.run_steps(self.step_budget) == Event::StepLimit {
+                debug!(
+                    name: "over_budget",
+                    target: "intcode::packet",
+                    address,
+                    steps = self.step_budget
+                );
+                round.over_budget.push(address as C);
+            }
+
+            let complete = machine.outputs.len() / 3 * 3;
+            let packets: Vec<Packet> = machine
+                .outputs
+                .drain(..complete)
+                .collect::<Vec<C>>()
+                .chunks(3)
+                .map(|triple| Packet {
+                    from: address as C,
+                    to: triple[0],
+                    x: triple[1],
+                    y: triple[2],
+                })
+                .collect();
+            round.sent.extend(packets.iter().cloned());
+            self.route(packets);
+        }
+        self.rounds += 1;
+
+        round.idle = quiet && round.sent.is_empty() && round.over_budget.is_empty();
+        self.idle_rounds = if round.idle { self.idle_rounds + 1 } else { 0 };
+        if self.idle_rounds >= self.idle_after {
+            self.idle_rounds = 0;
+            for handler in self.specials.values_mut() {
+                round.woken.extend(handler.idle());
+            }
+            self.route(round.woken.clone());
+        }
+        round
+    }
+
+    /// Step until `done` returns true for a round, returning that round, or
+    /// `None` if every machine halts or traps first. A machine that never
+    /// waits for input keeps this going; check `Round::over_budget` in `done`
+    /// to give up on it.
+    pub fn run_until<F: FnMut(&Round) -> bool>(&mut self, mut done: F) -> Option<Round> {
+        while !self.machines.iter().all(stopped) {
+            let round = self.step();
+            if done(&round) {
+                return Some(round);
+            }
+        }
+        None
+    }
+}
+
+fn stopped(machine: &Computer) -> bool {
+    matches!(
+        machine.state,
+        State::Halted | State::Trapped(_) | State::Fault(_)
+    )
+}
@@ -0,0 +1,57 @@
+use crate::{Computer, Event, C};
+
+/// Lazily runs a computer, yielding each output as it is produced. See
+/// `Computer::outputs_iter`.
+///
+/// Yields `Event::Output` for every value, then at most one of
+/// `Event::AwaitingInput`, `Event::Trapped` or `Event::Fault` before ending;
+/// a halt just ends it. After `AwaitingInput`, `feed` more input to keep
+/// going.
+#[derive(Debug)]
+pub struct Outputs<'a> {
+    computer: &'a mut Computer,
+    done: bool,
+}
+
+impl<'a> Outputs<'a> {
+    pub(crate) fn new(computer: &'a mut Computer) -> Self {
+        Outputs {
+            computer,
+            done: false,
+        }
+    }
+
+    /// Queue more input, e.g. after the machine asked for it.
+    pub fn feed<I: IntoIterator<Item = C>>(&mut self, values: I) {
+        self.computer.queue_inputs(values);
+        self.done = false;
+    }
+
+    pub fn computer(&self) -> &Computer {
+        self.computer
+    }
+}
+
+impl Iterator for Outputs<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        if self.done {
+            return None;
+        }
+        match self.computer.run_to_event() {
+            Event::Output(value) => {
+                self.computer.outputs.pop();
+                Some(Event::Output(value))
+            }
+            Event::Halted => {
+                self.done = true;
+                None
+            }
+            other => {
+                self.done = true;
+                Some(other)
+            }
+        }
+    }
+}
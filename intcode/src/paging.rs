@@ -0,0 +1,161 @@
+use crate::{Memory, C};
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Index;
+use std::sync::Arc;
+
+/// Words per page.
+pub const PAGE_SIZE: usize = 256;
+
+type Page = [C; PAGE_SIZE];
+
+/// Memory split into reference-counted pages. Cloning only copies the page
+/// pointers; a page is copied the first time one of its sharers writes to
+/// it. Words past `len` in the last page are always zero.
+#[derive(Clone, Default)]
+pub struct PagedMemory {
+    pages: Vec<Arc<Page>>,
+    len: usize,
+}
+
+impl PagedMemory {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, addr: usize) -> Option<C> {
+        if addr < self.len {
+            Some(self.pages[addr / PAGE_SIZE][addr % PAGE_SIZE])
+        } else {
+            None
+        }
+    }
+
+    /// Store `value` at `addr`, copying its page first if it is shared.
+    /// Returns the old value.
+    ///
+    /// Panics if `addr` is out of range.
+    pub fn set(&mut self, addr: usize, value: C) -> C {
+        assert!(
+            addr < self.len,
+            "Address {} is out of range for memory of length {}",
+            addr,
+            self.len
+        );
+        let page = Arc::make_mut(&mut self.pages[addr / PAGE_SIZE]);
+        std::mem::replace(&mut page[addr % PAGE_SIZE], value)
+    }
+
+    /// Grow to `len` words. New pages all share one page of zeroes.
+    pub fn pad(&mut self, len: usize) {
+        if len <= self.len {
+            return;
+        }
+        let zero = Arc::new([0; PAGE_SIZE]);
+        self.pages.resize(len.div_ceil(PAGE_SIZE), zero);
+        self.len = len;
+    }
+
+    /// Up to `len` words starting at `addr`, fewer at the end of memory.
+    /// Borrowed unless the range crosses a page boundary.
+    pub fn window(&self, addr: usize, len: usize) -> Cow<'_, [C]> {
+        let end = self.len.min(addr.saturating_add(len));
+        if addr >= end {
+            return Cow::Borrowed(&[]);
+        }
+        let offset = addr % PAGE_SIZE;
+        if (end - 1) / PAGE_SIZE == addr / PAGE_SIZE {
+            let page = &self.pages[addr / PAGE_SIZE];
+            Cow::Borrowed(&page[offset..offset + end - addr])
+        } else {
+            Cow::Owned((addr..end).map(|addr| self[addr]).collect())
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = C> + '_ {
+        self.pages
+            .iter()
+            .flat_map(|page| page.iter().cloned())
+            .take(self.len)
+    }
+
+    pub fn to_vec(&self) -> Memory {
+        self.iter().collect()
+    }
+
+    /// Pages this memory still shares with `other`.
+    pub fn shared_pages(&self, other: &PagedMemory) -> usize {
+        self.pages
+            .iter()
+            .zip(other.pages.iter())
+            .filter(|(a, b)| Arc::ptr_eq(a, b))
+            .count()
+    }
+}
+
+impl From<Memory> for PagedMemory {
+    fn from(memory: Memory) -> Self {
+        let pages = memory
+            .chunks(PAGE_SIZE)
+            .map(|chunk| {
+                let mut page = [0; PAGE_SIZE];
+                page[..chunk.len()].copy_from_slice(chunk);
+                Arc::new(page)
+            })
+            .collect();
+        PagedMemory {
+            pages,
+            len: memory.len(),
+        }
+    }
+}
+
+impl From<&[C]> for PagedMemory {
+    fn from(memory: &[C]) -> Self {
+        PagedMemory::from(memory.to_vec())
+    }
+}
+
+impl Index<usize> for PagedMemory {
+    type Output = C;
+
+    fn index(&self, addr: usize) -> &C {
+        assert!(
+            addr < self.len,
+            "Address {} is out of range for memory of length {}",
+            addr,
+            self.len
+        );
+        &self.pages[addr / PAGE_SIZE][addr % PAGE_SIZE]
+    }
+}
+
+impl PartialEq for PagedMemory {
+    fn eq(&self, other: &Self) -> bool {
+        // the zero tail of the last page makes whole-page comparison safe
+        self.len == other.len
+            && self
+                .pages
+                .iter()
+                .zip(other.pages.iter())
+                .all(|(a, b)| Arc::ptr_eq(a, b) || a[..] == b[..])
+    }
+}
+
+impl Eq for PagedMemory {}
+
+impl PartialEq<Memory> for PagedMemory {
+    fn eq(&self, other: &Memory) -> bool {
+        self.len == other.len() && self.iter().eq(other.iter().cloned())
+    }
+}
+
+impl fmt::Debug for PagedMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{Computer, Graph, NodeId, TapId, C};
+use std::cmp::Ordering;
+use std::fmt;
+use std::thread;
+
+/// How a set of amplifiers is wired. Each stage gets its phase as its first
+/// input; `input` also gets the initial signal and the last value `output`
+/// writes is the result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topology {
+    pub stages: usize,
+    pub edges: Vec<(NodeId, NodeId)>,
+    pub input: NodeId,
+    pub output: NodeId,
+}
+
+impl Topology {
+    /// Each stage feeds the next.
+    pub fn pipeline(stages: usize) -> Self {
+        assert!(stages > 0, "A topology needs at least one stage");
+        Topology {
+            stages,
+            edges: (1..stages).map(|i| (i - 1, i)).collect(),
+            input: 0,
+            output: stages - 1,
+        }
+    }
+
+    /// A pipeline whose last stage feeds back into the first.
+    pub fn ring(stages: usize) -> Self {
+        let mut topology = Topology::pipeline(stages);
+        topology.edges.push((stages - 1, 0));
+        topology
+    }
+
+    /// One copy of `computer` per phase, wired up and with `output` tapped.
+    pub fn build(&self, computer: &Computer, phases: &[C], signal: C) -> (Graph, TapId) {
+        assert_eq!(phases.len(), self.stages, "Need one phase per stage");
+        let mut graph = Graph::new();
+        for (node, &phase) in phases.iter().enumerate() {
+            let inputs = if node == self.input {
+                vec![phase, signal]
+            } else {
+                vec![phase]
+            };
+            graph.add_node(computer.clone(), &inputs);
+        }
+        for &(from, to) in self.edges.iter() {
+            graph.connect(from, to);
+        }
+        let tap = graph.tap(self.output);
+        (graph, tap)
+    }
+
+    /// Run the circuit to completion and return its final output, if any.
+    pub fn evaluate(&self, computer: &Computer, phases: &[C], signal: C) -> Option<C> {
+        let (mut graph, tap) = self.build(computer, phases, signal);
+        graph.run();
+        graph.tapped(tap).last().cloned()
+    }
+}
+
+/// One configuration and the signal it produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranked {
+    pub phases: Vec<C>,
+    pub signal: C,
+}
+
+impl Ranked {
+    /// Highest signal first, ties broken by the phases so the order doesn't
+    /// depend on which thread found what.
+    fn rank(&self, other: &Ranked) -> Ordering {
+        other
+            .signal
+            .cmp(&self.signal)
+            .then_with(|| self.phases.cmp(&other.phases))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// The best configurations, best first.
+    pub top: Vec<Ranked>,
+    /// Configurations that ran, including ones that produced no output.
+    pub evaluated: usize,
+}
+
+impl SearchResult {
+    pub fn best(&self) -> Option<&Ranked> {
+        self.top.first()
+    }
+}
+
+/// More configurations than fit in a `usize`, so `PhaseSearch::run` can't
+/// number them to split the work.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TooManyConfigurations {
+    pub domain: usize,
+    pub stages: usize,
+}
+
+impl fmt::Display for TooManyConfigurations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Too many ways to assign {} phases to {} stages",
+            self.domain, self.stages
+        )
+    }
+}
+
+impl std::error::Error for TooManyConfigurations {}
+
+/// Try every assignment of distinct phases from `domain` to the stages of
+/// `topology` and rank them by output signal.
+#[derive(Debug, Clone)]
+pub struct PhaseSearch {
+    pub computer: Computer,
+    pub domain: Vec<C>,
+    pub topology: Topology,
+    pub signal: C,
+    pub threads: usize,
+    pub top: usize,
+}
+
+impl PhaseSearch {
+    pub fn new(computer: Computer, domain: &[C], topology: Topology) -> Self {
+        PhaseSearch {
+            computer,
+            domain: domain.to_vec(),
+            topology,
+            signal: 0,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            top: 1,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "Need at least one thread");
+        self.threads = threads;
+        self
+    }
+
+    /// Keep the `k` best configurations instead of just one.
+    pub fn with_top(mut self, k: usize) -> Self {
+        self.top = k;
+        self
+    }
+
+    pub fn with_signal(mut self, signal: C) -> Self {
+        self.signal = signal;
+        self
+    }
+
+    /// Number of configurations: k-permutations of the domain. `None` if
+    /// that overflows a `usize`.
+    pub fn configurations(&self) -> Option<usize> {
+        let n = self.domain.len();
+        let k = self.topology.stages;
+        if k > n {
+            return Some(0);
+        }
+        (n - k + 1..=n).try_fold(1usize, |count, i| count.checked_mul(i))
+    }
+
+    /// The `index`th of `total` configurations in lexicographic order of
+    /// domain positions.
+    fn configuration(&self, total: usize, mut index: usize) -> Vec<C> {
+        let mut remaining = self.domain.clone();
+        let mut radix = total;
+        let mut phases = Vec::with_capacity(self.topology.stages);
+        for _ in 0..self.topology.stages {
+            radix /= remaining.len();
+            phases.push(remaining.remove(index / radix));
+            index %= radix;
+        }
+        phases
+    }
+
+    pub fn run(&self) -> Result<SearchResult, TooManyConfigurations> {
+        let total = self.configurations().ok_or(TooManyConfigurations {
+            domain: self.domain.len(),
+            stages: self.topology.stages,
+        })?;
+        let threads = self.threads.min(total).max(1);
+        let mut top: Vec<Ranked> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|worker| {
+                    scope.spawn(move || {
+                        let mut top = Vec::new();
+                        for index in (worker..total).step_by(threads) {
+                            let phases = self.configuration(total, index);
+                            if let Some(signal) =
+                                self.topology.evaluate(&self.computer, &phases, self.signal)
+                            {
+                                top.push(Ranked { phases, signal });
+                                if top.len() > 2 * self.top.max(1) {
+                                    truncate(&mut top, self.top);
+                                }
+                            }
+                        }
+                        truncate(&mut top, self.top);
+                        top
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
+        truncate(&mut top, self.top);
+        Ok(SearchResult {
+            top,
+            evaluated: total,
+        })
+    }
+}
+
+fn truncate(ranked: &mut Vec<Ranked>, k: usize) {
+    ranked.sort_by(Ranked::rank);
+    ranked.truncate(k);
+}
@@ -0,0 +1,247 @@
+use crate::{Instruction, InstructionSet, Level, Opcode, Value, C};
+use std::collections::BTreeSet;
+use std::fmt;
+use Level::*;
+
+/// Parameter addressing modes, by their digit in the instruction word.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl Mode {
+    pub fn of(value: &Value) -> Mode {
+        match value {
+            Value::Pointer(_) => Mode::Position,
+            Value::Immediate(_) => Mode::Immediate,
+            Value::Relative(_) => Mode::Relative,
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Something a program did that its profile doesn't allow.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Violation {
+    Opcode(C),
+    Mode(Mode),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Opcode(code) => write!(f, "opcode {} is outside the profile", code),
+            Violation::Mode(mode) => write!(f, "{:?} mode is outside the profile", mode),
+        }
+    }
+}
+
+/// The dialect a computer is allowed to execute: which opcodes, which
+/// addressing modes, and whether memory extends past the program.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Profile {
+    opcodes: u128,
+    modes: u8,
+    pub extended_memory: bool,
+}
+
+impl Profile {
+    /// Nothing allowed; add to it with the `with_*` methods.
+    pub fn empty() -> Self {
+        Profile {
+            opcodes: 0,
+            modes: 0,
+            extended_memory: false,
+        }
+    }
+
+    /// Every opcode and mode, including registered ones.
+    pub fn all() -> Self {
+        Profile {
+            opcodes: u128::MAX,
+            modes: u8::MAX,
+            extended_memory: true,
+        }
+    }
+
+    pub fn day02() -> Self {
+        Profile::empty()
+            .with_opcodes(&[1, 2, 99])
+            .with_mode(Mode::Position)
+    }
+
+    pub fn day05() -> Self {
+        Profile::day02()
+            .with_opcodes(&[3, 4, 5, 6, 7, 8])
+            .with_mode(Mode::Immediate)
+    }
+
+    pub fn day09() -> Self {
+        let mut profile = Profile::day05()
+            .with_opcodes(&[9])
+            .with_mode(Mode::Relative);
+        profile.extended_memory = true;
+        profile
+    }
+
+    pub fn with_opcodes(mut self, codes: &[C]) -> Self {
+        for &code in codes {
+            assert!(
+                (0..100).contains(&code),
+                "Opcode {} is not a two-digit code",
+                code
+            );
+            self.opcodes |= 1 << code;
+        }
+        self
+    }
+
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.modes |= mode.bit();
+        self
+    }
+
+    pub fn allows_opcode(&self, code: C) -> bool {
+        (0..100).contains(&code) && self.opcodes & (1 << code) != 0
+    }
+
+    pub fn allows_mode(&self, mode: Mode) -> bool {
+        self.modes & mode.bit() != 0
+    }
+
+    pub fn check(&self, instr: &Instruction) -> Result<(), Violation> {
+        let code = instr.opcode.code();
+        if !self.allows_opcode(code) {
+            return Err(Violation::Opcode(code));
+        }
+        for arg in instr.args.iter() {
+            let mode = Mode::of(arg);
+            if !self.allows_mode(mode) {
+                return Err(Violation::Mode(mode));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Level> for Profile {
+    fn from(level: Level) -> Self {
+        match level {
+            Day02 => Profile::day02(),
+            Day05 => Profile::day05(),
+            Day09 => Profile::day09(),
+        }
+    }
+}
+
+/// What a program uses, found by following every path from address 0.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Requirements {
+    pub opcodes: BTreeSet<C>,
+    pub modes: BTreeSet<Mode>,
+    /// A position-mode operand points past the end of the program.
+    pub extended_memory: bool,
+    /// Jumps whose target is only known at run time.
+    pub unresolved_jumps: Vec<usize>,
+    /// Reachable addresses that don't decode, usually because the program
+    /// rewrites them before getting there.
+    pub undecodable: Vec<usize>,
+}
+
+impl Requirements {
+    /// The smallest standard level covering everything found, or `None` if
+    /// the program uses registered opcodes.
+    pub fn level(&self) -> Option<Level> {
+        let mut level = if self.extended_memory { Day09 } else { Day02 };
+        for &code in self.opcodes.iter() {
+            match code {
+                1..=9 | 99 => level = level.max(Opcode::new(code).level()?),
+                _ => return None,
+            }
+        }
+        for mode in self.modes.iter() {
+            level = level.max(match mode {
+                Mode::Position => Day02,
+                Mode::Immediate => Day05,
+                Mode::Relative => Day09,
+            });
+        }
+        Some(level)
+    }
+
+    /// Everything found that `profile` doesn't allow.
+    pub fn violations(&self, profile: &Profile) -> Vec<Violation> {
+        let opcodes = self
+            .opcodes
+            .iter()
+            .filter(|&&code| !profile.allows_opcode(code))
+            .map(|&code| Violation::Opcode(code));
+        let modes = self
+            .modes
+            .iter()
+            .filter(|&&mode| !profile.allows_mode(mode))
+            .map(|&mode| Violation::Mode(mode));
+        opcodes.chain(modes).collect()
+    }
+}
+
+/// Statically find the opcodes and modes `program` can reach.
+///
+/// Jumps with an immediate condition are treated as always or never taken,
+/// and position-mode jump targets are read from the initial image, so this
+/// assumes the program doesn't rewrite its own jump tables. Registered
+/// opcodes are assumed to fall through.
+pub fn analyze(program: &[C], instructions: &InstructionSet) -> Requirements {
+    let mut requirements = Requirements::default();
+    let mut seen = BTreeSet::new();
+    let mut pending = vec![0];
+    while let Some(addr) = pending.pop() {
+        if addr >= program.len() || !seen.insert(addr) {
+            continue;
+        }
+        let instr = match instructions.decode(program, addr) {
+            Ok(instr) => instr,
+            Err(_) => {
+                requirements.undecodable.push(addr);
+                continue;
+            }
+        };
+        requirements.opcodes.insert(instr.opcode.code());
+        for arg in instr.args.iter() {
+            requirements.modes.insert(Mode::of(arg));
+            if let Value::Pointer(target) = arg {
+                if *target >= program.len() {
+                    requirements.extended_memory = true;
+                }
+            }
+        }
+        let next = addr + instr.raw.len();
+        let (may_jump, may_fall) = match (instr.opcode, instr.args.first()) {
+            (Opcode::Exit, _) => (false, false),
+            (Opcode::JumpTrue, Some(Value::Immediate(v))) => (*v != 0, *v == 0),
+            (Opcode::JumpFalse, Some(Value::Immediate(v))) => (*v == 0, *v != 0),
+            (Opcode::JumpTrue, _) | (Opcode::JumpFalse, _) => (true, true),
+            _ => (false, true),
+        };
+        if may_fall {
+            pending.push(next);
+        }
+        if may_jump {
+            match instr.args[1] {
+                Value::Immediate(target) => pending.push(target as usize),
+                Value::Pointer(cell) if cell < program.len() => {
+                    pending.push(program[cell] as usize)
+                }
+                _ => requirements.unresolved_jumps.push(addr),
+            }
+        }
+    }
+    requirements.unresolved_jumps.sort_unstable();
+    requirements.undecodable.sort_unstable();
+    requirements
+}
@@ -0,0 +1,176 @@
+use crate::{Computer, DecodeError, Event, Violation, C};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::debug;
+
+const BLACK: C = 0;
+const WHITE: C = 1;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub fn left(self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn right(self) -> Self {
+        self.left().left().left()
+    }
+
+    /// (dx, dy) of one step, with y growing downwards.
+    pub fn delta(self) -> (C, C) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+}
+
+/// Why `Robot::run` stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RobotState {
+    Halted,
+    StepLimit,
+    /// The brain asked for input again before giving a colour and a turn.
+    Stalled,
+    Trapped(Violation),
+    Fault(DecodeError),
+    /// The brain asked to turn some way other than 0 or 1. Nothing was
+    /// painted for that step.
+    BadTurn(C),
+}
+
+/// Why the brain stopped before giving both a colour and a turn; the end of
+/// `Computer::outputs_iter` means it halted.
+fn stopped(event: Option<Event>) -> RobotState {
+    match event {
+        Some(Event::AwaitingInput) => RobotState::Stalled,
+        Some(Event::Trapped(violation)) => RobotState::Trapped(violation),
+        Some(Event::Fault(error)) => RobotState::Fault(error),
+        _ => RobotState::Halted,
+    }
+}
+
+/// A robot painting hull panels. Each step the brain is given the colour of
+/// the panel underneath (0 black, 1 white) and outputs the colour to paint
+/// it and which way to turn (0 left, 1 right) before moving one panel.
+#[derive(Debug, Clone)]
+pub struct Robot {
+    pub brain: Computer,
+    pub position: (C, C),
+    pub facing: Direction,
+    pub colors: BTreeMap<(C, C), C>,
+    pub painted: BTreeSet<(C, C)>,
+    pub steps: usize,
+    pub step_limit: Option<usize>,
+}
+
+impl Robot {
+    /// Starts at the origin facing up, over an all-black hull.
+    pub fn new(brain: Computer) -> Self {
+        Robot {
+            brain,
+            position: (0, 0),
+            facing: Direction::Up,
+            colors: BTreeMap::new(),
+            painted: BTreeSet::new(),
+            steps: 0,
+            step_limit: None,
+        }
+    }
+
+    pub fn with_start_color(mut self, color: C) -> Self {
+        self.colors.insert(self.position, color);
+        self
+    }
+
+    pub fn with_step_limit(mut self, steps: usize) -> Self {
+        self.step_limit = Some(steps);
+        self
+    }
+
+    pub fn color(&self, x: C, y: C) -> C {
+        self.colors.get(&(x, y)).cloned().unwrap_or(BLACK)
+    }
+
+    /// Paint, turn and move once. `None` means the robot can carry on.
+    pub fn step(&mut self) -> Option<RobotState> {
+        let (x, y) = self.position;
+        let mut outputs = self.brain.outputs_iter(Some(self.color(x, y)));
+        let color = match outputs.next() {
+            Some(Event::Output(color)) => color,
+            other => return Some(stopped(other)),
+        };
+        let turn = match outputs.next() {
+            Some(Event::Output(turn)) => turn,
+            other => return Some(stopped(other)),
+        };
+        debug!(
+            name: "paint",
+            target: "intcode::paint",
+            x,
+            y,
+            color,
+            turn
+        );
+        self.facing = match turn {
+            0 => self.facing.left(),
+            1 => self.facing.right(),
+            _ => return Some(RobotState::BadTurn(turn)),
+        };
+        self.colors.insert(self.position, color);
+        self.painted.insert(self.position);
+        let (dx, dy) = self.facing.delta();
+        self.position = (x + dx, y + dy);
+        self.steps += 1;
+        None
+    }
+
+    pub fn run(&mut self) -> RobotState {
+        loop {
+            if self.step_limit.is_some_and(|limit| self.steps >= limit) {
+                return RobotState::StepLimit;
+            }
+            if let Some(state) = self.step() {
+                return state;
+            }
+        }
+    }
+
+    /// The white panels, `#` on `.`, cropped to where they are.
+    pub fn render(&self) -> String {
+        let white: Vec<(C, C)> = self
+            .colors
+            .iter()
+            .filter(|(_, &color)| color == WHITE)
+            .map(|(&position, _)| position)
+            .collect();
+        if white.is_empty() {
+            return String::new();
+        }
+        let min_x = white.iter().map(|p| p.0).min().unwrap();
+        let max_x = white.iter().map(|p| p.0).max().unwrap();
+        let min_y = white.iter().map(|p| p.1).min().unwrap();
+        let max_y = white.iter().map(|p| p.1).max().unwrap();
+        let mut text = String::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                text.push(if self.color(x, y) == WHITE { '#' } else { '.' });
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
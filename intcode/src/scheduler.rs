@@ -0,0 +1,64 @@
+use crate::rng::Rng;
+use crate::NodeId;
+use std::collections::VecDeque;
+
+/// Decides which runnable machine goes next. `ready` is never empty and is in
+/// ascending order.
+pub trait Policy {
+    fn pick(&mut self, ready: &[NodeId]) -> NodeId;
+}
+
+/// Cycles through the machines in order, skipping ones that can't run.
+#[derive(Debug, Clone, Default)]
+pub struct RoundRobin {
+    next: NodeId,
+}
+
+impl Policy for RoundRobin {
+    fn pick(&mut self, ready: &[NodeId]) -> NodeId {
+        let node = match ready.iter().find(|&&node| node >= self.next) {
+            Some(&node) => node,
+            None => ready[0],
+        };
+        self.next = node + 1;
+        node
+    }
+}
+
+/// Runs machines in the order they became runnable.
+#[derive(Debug, Clone, Default)]
+pub struct FifoReady {
+    queue: VecDeque<NodeId>,
+}
+
+impl Policy for FifoReady {
+    fn pick(&mut self, ready: &[NodeId]) -> NodeId {
+        self.queue.retain(|node| ready.contains(node));
+        for &node in ready {
+            if !self.queue.contains(&node) {
+                self.queue.push_back(node);
+            }
+        }
+        self.queue.pop_front().unwrap()
+    }
+}
+
+/// Picks uniformly at random, reproducibly for a given seed.
+#[derive(Debug, Clone)]
+pub struct Random {
+    rng: Rng,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Policy for Random {
+    fn pick(&mut self, ready: &[NodeId]) -> NodeId {
+        ready[self.rng.below(ready.len())]
+    }
+}
@@ -0,0 +1,329 @@
+use crate::{Computer, Event, State, C};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+/// Order in which `Search` visits machine states.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Strategy {
+    BreadthFirst,
+    DepthFirst,
+    /// Depth-first with a depth limit raised by one until something is found.
+    IterativeDeepening,
+    /// Lowest heuristic first, ties broken by input sequence.
+    BestFirst,
+}
+
+/// A state that satisfied the goal, and how it was reached.
+#[derive(Debug, Clone)]
+pub struct Found {
+    /// Indices into the alphabet, in the order they were fed.
+    pub moves: Vec<usize>,
+    /// Every input value fed, in order.
+    pub inputs: Vec<C>,
+    pub computer: Computer,
+    /// States generated before this one.
+    pub explored: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum SearchOutcome {
+    Found(Box<Found>),
+    /// Every reachable state was visited without meeting the goal.
+    Exhausted {
+        explored: usize,
+    },
+    /// `max_states` states were generated without meeting the goal.
+    StateLimit {
+        explored: usize,
+    },
+}
+
+impl SearchOutcome {
+    pub fn found(self) -> Option<Found> {
+        match self {
+            SearchOutcome::Found(found) => Some(*found),
+            _ => None,
+        }
+    }
+}
+
+struct Node {
+    computer: Computer,
+    moves: Vec<usize>,
+}
+
+type Key<'a> = Box<dyn Fn(&Computer) -> u64 + 'a>;
+type Heuristic<'a> = Box<dyn Fn(&Computer) -> i64 + 'a>;
+
+/// Searches the machine states reachable by feeding sequences of symbols
+/// from an alphabet, each symbol being one or more input values. After each
+/// symbol the machine runs until it waits for input again or stops; stopped
+/// machines can meet the goal but aren't expanded further.
+pub struct Search<'a> {
+    alphabet: Vec<Vec<C>>,
+    goal: Box<dyn Fn(&Computer) -> bool + 'a>,
+    strategy: Strategy,
+    key: Option<Key<'a>>,
+    heuristic: Option<Heuristic<'a>>,
+    pub max_states: Option<usize>,
+    pub max_depth: Option<usize>,
+    /// Instructions allowed per symbol; a machine going over is dropped.
+    pub max_steps: Option<usize>,
+}
+
+impl<'a> Search<'a> {
+    pub fn new<G>(alphabet: &[Vec<C>], goal: G) -> Self
+    where
+        G: Fn(&Computer) -> bool + 'a,
+    {
+        Search {
+            alphabet: alphabet.to_vec(),
+            goal: Box::new(goal),
+            strategy: Strategy::BreadthFirst,
+            key: None,
+            heuristic: None,
+            max_states: None,
+            max_depth: None,
+            max_steps: None,
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Lower is more promising. Required by `BestFirst`.
+    pub fn with_heuristic<H: Fn(&Computer) -> i64 + 'a>(mut self, heuristic: H) -> Self {
+        self.heuristic = Some(Box::new(heuristic));
+        self
+    }
+
+    /// Skip states whose key has been seen before.
+    pub fn with_dedup<K: Fn(&Computer) -> u64 + 'a>(mut self, key: K) -> Self {
+        self.key = Some(Box::new(key));
+        self
+    }
+
+    /// Skip states whose memory, registers and pending inputs have been seen
+    /// before, whatever they output on the way; see
+    /// `Computer::machine_fingerprint`.
+    pub fn with_machine_dedup(self) -> Self {
+        self.with_dedup(Computer::machine_fingerprint)
+    }
+
+    pub fn with_max_states(mut self, states: usize) -> Self {
+        self.max_states = Some(states);
+        self
+    }
+
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    pub fn with_max_steps(mut self, steps: usize) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    /// Run `computer` until it waits or stops, within the step budget.
+    fn settle(&self, computer: &mut Computer) -> bool {
+        match self.max_steps {
+            Some(steps) => computer.run_steps(steps) != Event::StepLimit,
+            None => {
+                computer.run_to_halt();
+                true
+            }
+        }
+    }
+
+    fn children(&self, node: &Node) -> Vec<Node> {
+        if node.computer.state != State::AwaitingInput {
+            return Vec::new();
+        }
+        self.alphabet
+            .iter()
+            .enumerate()
+            .filter_map(|(i, symbol)| {
+                let mut computer = node.computer.clone();
+                computer.queue_inputs(symbol.iter().cloned());
+                if !self.settle(&mut computer) {
+                    return None;
+                }
+                let mut moves = node.moves.clone();
+                moves.push(i);
+                Some(Node { computer, moves })
+            })
+            .collect()
+    }
+
+    fn found(&self, node: Node, explored: usize) -> Found {
+        let inputs = node
+            .moves
+            .iter()
+            .flat_map(|&i| self.alphabet[i].iter().cloned())
+            .collect();
+        Found {
+            moves: node.moves,
+            inputs,
+            computer: node.computer,
+            explored,
+        }
+    }
+
+    /// Search from `start`, which is first run until it waits for input.
+    pub fn run(&self, start: &Computer) -> SearchOutcome {
+        let mut computer = start.clone();
+        if !self.settle(&mut computer) {
+            return SearchOutcome::Exhausted { explored: 0 };
+        }
+        let root = Node {
+            computer,
+            moves: Vec::new(),
+        };
+        match self.strategy {
+            Strategy::IterativeDeepening => {
+                let mut explored = 0;
+                let mut depth = 0;
+                loop {
+                    let (outcome, cut_off) = self.walk(&root, Some(depth), explored);
+                    match outcome {
+                        SearchOutcome::Exhausted { explored: total } => explored = total,
+                        other => return other,
+                    }
+                    if !cut_off || self.max_depth.is_some_and(|max| depth >= max) {
+                        return SearchOutcome::Exhausted { explored };
+                    }
+                    depth += 1;
+                }
+            }
+            _ => self.walk(&root, self.max_depth, 0).0,
+        }
+    }
+
+    /// One pass of the configured strategy, depth-first for iterative
+    /// deepening. Also reports whether any state was cut off by `depth`.
+    fn walk(&self, root: &Node, depth: Option<usize>, explored: usize) -> (SearchOutcome, bool) {
+        let mut explored = explored;
+        let mut cut_off = false;
+        // depth-first can reach a state deep before reaching it shallow, so
+        // remember the depth each one was seen at
+        let mut seen: HashMap<u64, usize> = HashMap::new();
+        let mut frontier = Frontier::new(self.strategy);
+        let start = Node {
+            computer: root.computer.clone(),
+            moves: Vec::new(),
+        };
+        frontier.extend(vec![(start, self.score(&root.computer))]);
+        while let Some(node) = frontier.pop() {
+            if let Some(key) = self.key.as_ref() {
+                let key = key(&node.computer);
+                match seen.get(&key) {
+                    Some(&at) if at <= node.moves.len() => continue,
+                    _ => seen.insert(key, node.moves.len()),
+                };
+            }
+            if (self.goal)(&node.computer) {
+                return (
+                    SearchOutcome::Found(Box::new(self.found(node, explored))),
+                    cut_off,
+                );
+            }
+            if depth.is_some_and(|depth| node.moves.len() >= depth) {
+                cut_off |= node.computer.state == State::AwaitingInput;
+                continue;
+            }
+            let mut children = Vec::new();
+            for child in self.children(&node) {
+                if self.max_states.is_some_and(|max| explored >= max) {
+                    return (SearchOutcome::StateLimit { explored }, cut_off);
+                }
+                explored += 1;
+                let score = self.score(&child.computer);
+                children.push((child, score));
+            }
+            frontier.extend(children);
+        }
+        (SearchOutcome::Exhausted { explored }, cut_off)
+    }
+
+    fn score(&self, computer: &Computer) -> i64 {
+        match (&self.heuristic, self.strategy) {
+            (Some(heuristic), _) => heuristic(computer),
+            (None, Strategy::BestFirst) => panic!("Best-first search needs a heuristic"),
+            (None, _) => 0,
+        }
+    }
+}
+
+/// A node on the best-first heap, ordered lowest score first and then by
+/// input sequence.
+struct Scored {
+    score: i64,
+    node: Node,
+}
+
+impl Scored {
+    fn key(&self) -> Reverse<(i64, &Vec<usize>)> {
+        Reverse((self.score, &self.node.moves))
+    }
+}
+
+impl PartialEq for Scored {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+enum Frontier {
+    Queue(VecDeque<Node>),
+    Stack(Vec<Node>),
+    Heap(BinaryHeap<Scored>),
+}
+
+impl Frontier {
+    fn new(strategy: Strategy) -> Self {
+        match strategy {
+            Strategy::BreadthFirst => Frontier::Queue(VecDeque::new()),
+            Strategy::DepthFirst | Strategy::IterativeDeepening => Frontier::Stack(Vec::new()),
+            Strategy::BestFirst => Frontier::Heap(BinaryHeap::new()),
+        }
+    }
+
+    /// Add siblings, given in alphabet order.
+    fn extend(&mut self, nodes: Vec<(Node, i64)>) {
+        match self {
+            Frontier::Queue(queue) => queue.extend(nodes.into_iter().map(|(node, _)| node)),
+            // reversed so that they come off the stack in alphabet order
+            Frontier::Stack(stack) => stack.extend(nodes.into_iter().rev().map(|(node, _)| node)),
+            Frontier::Heap(heap) => heap.extend(
+                nodes
+                    .into_iter()
+                    .map(|(node, score)| Scored { score, node }),
+            ),
+        }
+    }
+
+    fn pop(&mut self) -> Option<Node> {
+        match self {
+            Frontier::Queue(queue) => queue.pop_front(),
+            Frontier::Stack(stack) => stack.pop(),
+            Frontier::Heap(heap) => heap.pop().map(|scored| scored.node),
+        }
+    }
+}
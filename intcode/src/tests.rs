@@ -0,0 +1,1469 @@
+use super::*;
+use std::io;
+use std::sync::Arc;
+
+#[test]
+fn test_run() {
+    let codes = vec![
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ];
+    let mut program = Computer::new(codes.clone());
+    program.inputs.push(7);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![999]);
+
+    let mut program = Computer::new(codes.clone());
+    program.inputs.push(8);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1000]);
+
+    let mut program = Computer::new(codes.clone());
+    program.inputs.push(9);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1001]);
+}
+
+#[test]
+fn test_add_and_mul() {
+    let program = vec![1, 0, 0, 0, 99];
+    let result = Computer::new(program.clone()).run();
+    assert_eq!(
+        &result.memory.window(0, program.len())[..],
+        &vec![2, 0, 0, 0, 99][..]
+    );
+
+    let program = vec![2, 3, 0, 3, 99];
+    let result = Computer::new(program.clone()).run();
+    assert_eq!(
+        &result.memory.window(0, program.len())[..],
+        &vec![2, 3, 0, 6, 99][..]
+    );
+
+    let program = vec![2, 4, 4, 5, 99, 0];
+    let result = Computer::new(program.clone()).run();
+    assert_eq!(
+        &result.memory.window(0, program.len())[..],
+        &vec![2, 4, 4, 5, 99, 9801][..]
+    );
+
+    let program = vec![1, 1, 1, 4, 99, 5, 6, 0, 99];
+    let result = Computer::new(program.clone()).run();
+    assert_eq!(
+        &result.memory.window(0, program.len())[..],
+        &vec![30, 1, 1, 4, 2, 5, 6, 0, 99][..]
+    );
+}
+
+#[test]
+fn test_equal_position() {
+    let mut program = Computer::new(vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+    program.inputs.push(8);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+
+    let mut program = Computer::new(vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+    program.inputs.push(7);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+}
+
+#[test]
+fn test_lt_position() {
+    let mut program = Computer::new(vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
+    program.inputs.push(7);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+
+    let mut program = Computer::new(vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
+    program.inputs.push(8);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    let mut program = Computer::new(vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
+    program.inputs.push(9);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+}
+
+#[test]
+fn test_lt_immediate() {
+    let mut program = Computer::new(vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
+    program.inputs.push(7);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+
+    let mut program = Computer::new(vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
+    program.inputs.push(8);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    let mut program = Computer::new(vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
+    program.inputs.push(9);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+}
+
+#[test]
+fn test_equal_immediate() {
+    let mut program = Computer::new(vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
+    program.inputs.push(8);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+
+    let mut program = Computer::new(vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
+    program.inputs.push(7);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+}
+
+#[test]
+fn test_jmp_position() {
+    let mut program = Computer::new(vec![
+        3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9,
+    ]);
+    program.inputs.push(0);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    let mut program = Computer::new(vec![
+        3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9,
+    ]);
+    program.inputs.push(99);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+}
+
+#[test]
+fn test_jmp_immediate() {
+    let mut program = Computer::new(vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]);
+    program.inputs.push(0);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    let mut program = Computer::new(vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]);
+    program.inputs.push(99);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+}
+
+#[test]
+fn test_input_output() {
+    let mut program = Computer::new(vec![3, 0, 4, 0, 99]);
+    program.inputs.push(1337);
+    assert_eq!(program.inputs, vec![1337]);
+    assert_eq!(program.outputs, Vec::new());
+    let result = program.run();
+    assert_eq!(result.inputs, Vec::new());
+    assert_eq!(result.outputs, vec![1337]);
+
+    let mut program = Computer::new(vec![3, 2, 0, 0, 99]);
+    program.inputs.push(4);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![3]);
+}
+
+#[test]
+fn test_day5_modes_and_jumps() {
+    // Using position mode, consider whether the input is equal to 8; output 1 (if it is) or 0 (if it is not).
+    let mut program = Computer::new(vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+    program.inputs.push(8);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+
+    let mut program = Computer::new(vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+    program.inputs.push(1337);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    // Using position mode, consider whether the input is less than 8; output 1 (if it is) or 0 (if it is not).
+    let mut program = Computer::new(vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
+    program.inputs.push(7);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+
+    let mut program = Computer::new(vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8]);
+    program.inputs.push(8);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    // Using immediate mode, consider whether the input is equal to 8; output 1 (if it is) or 0 (if it is not).
+    let mut program = Computer::new(vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
+    program.inputs.push(8);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+
+    let mut program = Computer::new(vec![3, 3, 1108, -1, 8, 3, 4, 3, 99]);
+    program.inputs.push(1337);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    // Using immediate mode, consider whether the input is less than 8; output 1 (if it is) or 0 (if it is not).
+    let mut program = Computer::new(vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
+    program.inputs.push(7);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+
+    let mut program = Computer::new(vec![3, 3, 1107, -1, 8, 3, 4, 3, 99]);
+    program.inputs.push(8);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    //Here are some jump tests that take an input, then output 0 if the input was zero or 1 if the input was non-zero:
+    let mut program = Computer::new(vec![
+        3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9,
+    ]);
+    program.inputs.push(0);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    let mut program = Computer::new(vec![
+        3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9,
+    ]);
+    program.inputs.push(1337);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+
+    let mut program = Computer::new(vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]);
+    program.inputs.push(0);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![0]);
+
+    let mut program = Computer::new(vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]);
+    program.inputs.push(1337);
+    let result = program.run();
+    assert_eq!(result.outputs, vec![1]);
+}
+
+#[test]
+fn test_day9_part1() {
+    let computer = Computer::new(vec![
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ]);
+    let result = computer.run();
+    assert_eq!(
+        result.outputs,
+        vec![109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99]
+    );
+
+    let computer = Computer::new(vec![104, 1125899906842624, 99]);
+    let result = computer.run();
+    assert_eq!(result.outputs, vec![1125899906842624]);
+
+    let computer = Computer::new(vec![1102, 34915192, 34915192, 7, 4, 7, 99, 0]);
+    let result = computer.run();
+    assert_eq!(format!("{}", result.outputs[0]).len(), 16);
+}
+
+#[test]
+fn test_levels() {
+    let result = Computer::with_level(vec![1, 0, 0, 0, 99], Level::Day02).run();
+    assert_eq!(result.memory, vec![2, 0, 0, 0, 99]);
+
+    let result = Computer::new(vec![1101, 1, 2, 100, 99]).run();
+    assert_eq!(result.memory.len(), 2048);
+    assert_eq!(result.memory[100], 3);
+
+    assert_eq!(Opcode::new(2).level(), Some(Level::Day02));
+    assert_eq!(Opcode::new(8).level(), Some(Level::Day05));
+    assert_eq!(Opcode::new(9).level(), Some(Level::Day09));
+    assert_eq!(Value::from(7, 1).unwrap().level(), Level::Day05);
+    assert_eq!(Value::from(7, 2).unwrap().level(), Level::Day09);
+}
+
+fn with_division() -> Arc<InstructionSet> {
+    let mut set = InstructionSet::standard();
+    set.register(10, "div", &[Role::Read, Role::Read, Role::Write], |call| {
+        let quotient = call.read(0) / call.read(1);
+        call.write(2, quotient);
+    });
+    set.register(11, "jz", &[Role::Read, Role::Read], |call| {
+        if call.read(0) == 0 {
+            let to = call.read(1);
+            call.jump(to as usize);
+        }
+    });
+    set.register(12, "echo", &[], |call| match call.computer.inputs.pop() {
+        Some(value) => call.computer.outputs.push(value),
+        None => call.wait(),
+    });
+    Arc::new(set)
+}
+
+#[test]
+fn test_custom_opcodes() {
+    let program = vec![1110, 17, 5, 7, 4, 7, 99, 0];
+    let result = Computer::with_instructions(program, Profile::all(), with_division()).run();
+    assert_eq!(result.outputs, vec![3]);
+
+    // jz 0, 6 skips the output of 1
+    let program = vec![1111, 0, 6, 104, 1, 99, 104, 2, 99];
+    let result = Computer::with_instructions(program, Profile::all(), with_division()).run();
+    assert_eq!(result.outputs, vec![2]);
+
+    let program = vec![12, 12, 99];
+    let computer = Computer::with_instructions(program, Profile::all(), with_division());
+    let mut result = computer.run();
+    assert_eq!(result.state, State::AwaitingInput);
+    assert_eq!(result.eip, 0);
+    result.inputs.push(5);
+    result.step_mut();
+    assert_eq!(result.state, State::Running);
+    result.inputs.push(6);
+    let result = result.run();
+    assert_eq!(result.state, State::Halted);
+    assert_eq!(result.outputs, vec![5, 6]);
+}
+
+#[test]
+fn test_unregistered_opcode() {
+    let result = Computer::new(vec![1110, 17, 5, 7, 4, 7, 99, 0]).run();
+    assert_eq!(result.state, State::Fault(DecodeError::UnknownOpcode(10)));
+    assert_eq!(result.eip, 0);
+}
+
+#[test]
+fn test_faults() {
+    for (program, error) in [
+        (vec![104, 1, 301, 0, 0, 0, 99], DecodeError::BadMode(3)),
+        (
+            vec![104, 1, 11101, 1, 2, 3, 99],
+            DecodeError::ImmediateWrite(2),
+        ),
+        (vec![104, 1, 1, 0], DecodeError::Truncated),
+    ]
+    .iter()
+    {
+        let mut computer = Computer::with_level(program.clone(), Level::Day05);
+        assert_eq!(computer.run_to_event(), Event::Output(1), "{:?}", program);
+        assert_eq!(
+            computer.run_to_halt(),
+            Event::Fault(*error),
+            "{:?}",
+            program
+        );
+        assert_eq!(computer.last.raw, vec![104, 1]);
+    }
+
+    // one bad machine stops the graph instead of the process
+    let mut graph = Graph::new();
+    let good = graph.add_node(Computer::new(vec![3, 0, 99]), &[]);
+    let bad = graph.add_node(Computer::new(vec![42, 99]), &[]);
+    graph.connect(bad, good);
+    assert_eq!(graph.run(), GraphState::Fault(bad));
+}
+
+#[test]
+#[should_panic(expected = "already registered")]
+fn test_register_builtin() {
+    InstructionSet::standard().register(1, "add", &[], |_| {});
+}
+
+#[test]
+fn test_disassemble() {
+    let text: Vec<String> = disassemble(&[1110, 17, 5, 7, 204, -1, 99, 0], &with_division())
+        .into_iter()
+        .map(|line| line.text)
+        .collect();
+    assert_eq!(text, vec!["div 17, 5, [7]", "out [rb-1]", "halt", "data 0"]);
+
+    let lines = disassemble(&[1101, 1, 2, 5, 301, 0], &InstructionSet::standard());
+    assert_eq!(
+        lines[0].to_string().trim_end(),
+        "0000  1101,1,2,5               add 1, 2, [5]"
+    );
+    assert_eq!(lines[1].text, "data 301");
+    assert_eq!(lines[2].addr, 5);
+}
+
+#[test]
+fn test_profiles() {
+    let result = Computer::with_level(vec![3, 0, 99], Level::Day02).run();
+    assert_eq!(result.state, State::Trapped(Violation::Opcode(3)));
+    assert_eq!(result.eip, 0);
+
+    let result = Computer::with_level(vec![1101, 1, 2, 0, 99], Level::Day02).run();
+    assert_eq!(
+        result.state,
+        State::Trapped(Violation::Mode(Mode::Immediate))
+    );
+
+    let result = Computer::with_level(vec![1101, 1, 2, 0, 109, 1, 99], Level::Day05).run();
+    assert_eq!(result.state, State::Trapped(Violation::Opcode(9)));
+    assert_eq!(result.eip, 4);
+    assert_eq!(result.memory[0], 3);
+    // a trap doesn't replace the last instruction executed
+    assert_eq!(result.last.raw, vec![1101, 1, 2, 0]);
+
+    let result = Computer::with_level(vec![204, 0, 99], Level::Day05).run();
+    assert_eq!(
+        result.state,
+        State::Trapped(Violation::Mode(Mode::Relative))
+    );
+
+    let custom = Profile::day02().with_opcodes(&[4]);
+    let result = Computer::with_profile(vec![4, 0, 99], custom).run();
+    assert_eq!(result.state, State::Halted);
+    assert_eq!(result.outputs, vec![4]);
+}
+
+#[test]
+fn test_analyze() {
+    let standard = InstructionSet::standard();
+    let level = |text: &str| analyze(&parse(text), &standard).level();
+    assert_eq!(
+        level(include_str!("../../day02/input.txt")),
+        Some(Level::Day02)
+    );
+    assert_eq!(
+        level(include_str!("../../day05/input.txt")),
+        Some(Level::Day05)
+    );
+    assert_eq!(
+        level(include_str!("../../day07/input.txt")),
+        Some(Level::Day05)
+    );
+    assert_eq!(
+        level(include_str!("../../day09/input.txt")),
+        Some(Level::Day09)
+    );
+
+    let requirements = analyze(&[1105, 1, 7, 109, 1, 204, 0, 99], &standard);
+    assert_eq!(requirements.level(), Some(Level::Day05));
+    assert_eq!(
+        requirements.violations(&Profile::day02()),
+        vec![Violation::Opcode(5), Violation::Mode(Mode::Immediate)]
+    );
+
+    let requirements = analyze(&[1110, 17, 5, 7, 99], &with_division());
+    assert_eq!(requirements.level(), None);
+}
+
+fn parse(text: &str) -> Memory {
+    parse_program(text).unwrap()
+}
+
+/// Prints "> ", echoes input up to a newline, then prints 1000.
+const ECHO: [C; 18] = [
+    104, 62, 104, 32, 3, 100, 4, 100, 1008, 100, 10, 101, 1006, 101, 4, 104, 1000, 99,
+];
+
+#[test]
+fn test_ascii() {
+    let mut ascii = Ascii::new(Computer::new(ECHO.to_vec()));
+    assert_eq!(ascii.read_until("> ").text, "> ");
+    assert_eq!(ascii.run().text, "");
+    assert_eq!(ascii.state(), &State::AwaitingInput);
+
+    ascii.send_line("hi");
+    let text = ascii.run();
+    assert_eq!(text.text, "hi\n");
+    assert_eq!(text.values, vec![1000]);
+    assert_eq!(ascii.state(), &State::Halted);
+
+    let mut ascii = Ascii::new(Computer::new(vec![104, 97, 104, 10, 104, 98, 104, 10, 99]));
+    assert_eq!(ascii.read_line().text, "a\n");
+    assert_eq!(ascii.read_line().text, "b\n");
+    assert_eq!(ascii.read_line(), Text::default());
+}
+
+#[test]
+fn test_ascii_interact() {
+    let mut ascii = Ascii::new(Computer::new(ECHO.to_vec()));
+    let mut output = Vec::new();
+    let state = ascii
+        .interact(&b"hello\r\nignored\n"[..], &mut output)
+        .unwrap();
+    assert_eq!(state, State::Halted);
+    assert_eq!(String::from_utf8(output).unwrap(), "> hello\n1000\n");
+
+    let mut ascii = Ascii::new(Computer::new(ECHO.to_vec()));
+    let state = ascii.interact(&b""[..], io::sink()).unwrap();
+    assert_eq!(state, State::AwaitingInput);
+}
+
+/// Forwards every packet it receives to the address in cell 54, adding one
+/// to Y on the way.
+fn forwarder(to: C) -> Memory {
+    let mut program = vec![
+        3, 50, 3, 51, 1008, 51, -1, 53, 1005, 53, 2, 3, 52, 1001, 52, 1, 52, 4, 54, 4, 51, 4, 52,
+        1105, 1, 2,
+    ];
+    program.resize(55, 0);
+    program[54] = to;
+    program
+}
+
+struct Recorder(Arc<std::sync::Mutex<Vec<Packet>>>);
+
+impl Special for Recorder {
+    fn receive(&mut self, packet: Packet) -> Vec<Packet> {
+        self.0.lock().unwrap().push(packet);
+        Vec::new()
+    }
+}
+
+#[test]
+fn test_network() {
+    let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let machines = vec![
+        Computer::new(forwarder(1)),
+        Computer::new(forwarder(2)),
+        Computer::new(forwarder(255)),
+    ];
+    let mut network =
+        Network::from_machines(machines).with_special(255, Recorder(received.clone()));
+    assert!(network.step().idle);
+
+    network.send(Packet {
+        from: -1,
+        to: 0,
+        x: 7,
+        y: 0,
+    });
+    let round = network.step();
+    assert!(!round.idle);
+    let hops: Vec<(C, C, C)> = round.sent.iter().map(|p| (p.from, p.to, p.y)).collect();
+    assert_eq!(hops, vec![(0, 1, 1), (1, 2, 2), (2, 255, 3)]);
+    assert_eq!(received.lock().unwrap().len(), 1);
+    assert_eq!(received.lock().unwrap()[0].x, 7);
+    assert!(network.step().idle);
+
+    network.send(Packet {
+        from: -1,
+        to: 9,
+        x: 0,
+        y: 0,
+    });
+    assert_eq!(network.dropped.len(), 1);
+}
+
+#[test]
+fn test_network_preloaded() {
+    // sends (its address, the next input) to 255
+    let mut machine = Computer::new(vec![3, 100, 3, 101, 104, 255, 4, 100, 4, 101, 99]);
+    machine.queue_inputs(Some(42));
+    let mut network = Network::from_machines(vec![machine]);
+    network.step();
+    let dropped: Vec<(C, C, C)> = network.dropped.iter().map(|p| (p.to, p.x, p.y)).collect();
+    assert_eq!(dropped, vec![(255, 0, 42)]);
+}
+
+#[test]
+fn test_network_step_budget() {
+    // reads its address, then spins without ever asking for more
+    let spinner = Computer::new(vec![3, 100, 1105, 1, 2]);
+    let mut network =
+        Network::from_machines(vec![Computer::new(forwarder(255)), spinner]).with_step_budget(50);
+    let round = network.step();
+    assert_eq!(round.over_budget, vec![1]);
+    assert!(!round.idle);
+    assert_eq!(network.machines[1].counter, 50);
+    let round = network.run_until(|round| round.over_budget.contains(&1));
+    assert_eq!(round.map(|round| round.over_budget), Some(vec![1]));
+    assert_eq!(network.machines[1].counter, 100);
+}
+
+#[test]
+fn test_network_nat() {
+    let mut network = Network::new(&forwarder(255), 2)
+        .with_special(255, Nat::new(255))
+        .with_idle_after(2);
+    network.send(Packet {
+        from: -1,
+        to: 0,
+        x: 5,
+        y: 0,
+    });
+    let mut woken = Vec::new();
+    let round = network.run_until(|round| {
+        woken.extend(round.woken.iter().map(|p| (p.from, p.to, p.y)));
+        woken.len() == 3
+    });
+    assert!(round.is_some());
+    assert_eq!(woken, vec![(255, 0, 1), (255, 0, 2), (255, 0, 3)]);
+    // one busy round then two idle ones per wake-up
+    assert_eq!(network.rounds, 9);
+}
+
+#[test]
+fn test_graph() {
+    let doubler = Computer::new(vec![3, 11, 1002, 11, 2, 11, 4, 11, 1105, 1, 0, 0]);
+    let adder = Computer::new(vec![
+        3, 13, 3, 14, 1, 13, 14, 15, 4, 15, 1105, 1, 0, 0, 0, 0,
+    ]);
+    let mut graph = Graph::new();
+    let source = graph.add_node(doubler.clone(), &[1, 2]);
+    let left = graph.add_node(doubler.clone(), &[]);
+    let right = graph.add_node(doubler, &[]);
+    let sink = graph.add_node(adder, &[]);
+    graph.connect(source, left);
+    graph.connect(source, right);
+    graph.connect(left, sink);
+    graph.connect(right, sink);
+    let tap = graph.tap(sink);
+
+    assert_eq!(graph.run(), GraphState::Deadlock);
+    assert_eq!(graph.tapped(tap), &[8, 16]);
+
+    graph.send(source, &[3]);
+    assert_eq!(graph.run(), GraphState::Deadlock);
+    assert_eq!(graph.tapped(tap), &[8, 16, 24]);
+    assert!(graph.edges.iter().all(|edge| edge.queue.is_empty()));
+
+    let mut graph = Graph::new();
+    graph.add_node(Computer::with_level(vec![3, 0, 99], Level::Day02), &[1]);
+    assert_eq!(graph.run(), GraphState::Trapped(0));
+}
+
+#[test]
+fn test_policies() {
+    let mut policy = RoundRobin::default();
+    let picks: Vec<NodeId> = [&[0, 1, 2][..], &[0, 1, 2], &[0, 2], &[0, 1]]
+        .iter()
+        .map(|ready| policy.pick(ready))
+        .collect();
+    assert_eq!(picks, vec![0, 1, 2, 0]);
+
+    let mut policy = FifoReady::default();
+    let picks: Vec<NodeId> = [&[0, 1][..], &[0, 1, 2], &[0, 2], &[0, 1, 2]]
+        .iter()
+        .map(|ready| policy.pick(ready))
+        .collect();
+    assert_eq!(picks, vec![0, 1, 0, 2]);
+
+    let picks = |seed| {
+        let mut policy = Random::new(seed);
+        (0..20).map(|_| policy.pick(&[3, 5, 8])).collect::<Vec<_>>()
+    };
+    assert_eq!(picks(7), picks(7));
+    assert!(picks(7).iter().all(|node| [3, 5, 8].contains(node)));
+}
+
+#[test]
+fn test_graph_policies() {
+    let amplifier = Computer::new(vec![
+        3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28,
+        1005, 28, 6, 99, 0, 0, 5,
+    ]);
+    let ring = || {
+        let mut graph = Graph::new();
+        let nodes: Vec<NodeId> = [9, 8, 7, 6, 5]
+            .iter()
+            .enumerate()
+            .map(|(i, &phase)| match i {
+                0 => graph.add_node(amplifier.clone(), &[phase, 0]),
+                _ => graph.add_node(amplifier.clone(), &[phase]),
+            })
+            .collect();
+        graph.ring(&nodes);
+        let tap = graph.tap(4);
+        (graph, tap)
+    };
+    let mut policies: Vec<Box<dyn Policy>> = vec![
+        Box::new(RoundRobin::default()),
+        Box::new(FifoReady::default()),
+        Box::new(Random::new(1)),
+        Box::new(Random::new(2)),
+    ];
+    for policy in policies.iter_mut() {
+        let (mut graph, tap) = ring();
+        assert_eq!(graph.run_with(policy.as_mut()), GraphState::Halted);
+        assert_eq!(graph.tapped(tap).last(), Some(&139629729));
+    }
+
+    // without the initial signal every amplifier waits on the previous one
+    let (mut graph, _) = ring();
+    graph.nodes[0].inputs.clear();
+    graph.nodes[0].inputs.push(9);
+    assert_eq!(graph.run(), GraphState::Deadlock);
+    assert!(graph.nodes.iter().all(|n| n.state == State::AwaitingInput));
+}
+
+#[test]
+fn test_phase_search() {
+    // output = input * 10 + phase
+    let amplifier = Computer::new(vec![
+        3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+    ]);
+    let search = PhaseSearch::new(amplifier, &[0, 1, 2, 3, 4, 5, 6], Topology::pipeline(5));
+    assert_eq!(search.configurations(), Some(2520));
+
+    let result = search.clone().with_top(4).with_threads(1).run().unwrap();
+    assert_eq!(result.evaluated, 2520);
+    let top: Vec<C> = result.top.iter().map(|r| r.signal).collect();
+    assert_eq!(top, vec![65432, 65431, 65430, 65423]);
+    assert_eq!(result.best().unwrap().phases, vec![6, 5, 4, 3, 2]);
+    for threads in [2, 3, 8].iter() {
+        assert_eq!(
+            search.clone().with_top(4).with_threads(*threads).run(),
+            Ok(result.clone())
+        );
+    }
+
+    let too_few = PhaseSearch::new(Computer::new(vec![99]), &[0, 1], Topology::ring(3))
+        .run()
+        .unwrap();
+    assert_eq!(too_few.evaluated, 0);
+    assert_eq!(too_few.best(), None);
+
+    // 30! overflows, so nothing gets spawned
+    let domain: Vec<C> = (0..30).collect();
+    let too_many = PhaseSearch::new(Computer::new(vec![99]), &domain, Topology::pipeline(30));
+    assert_eq!(too_many.configurations(), None);
+    assert_eq!(
+        too_many.run(),
+        Err(TooManyConfigurations {
+            domain: 30,
+            stages: 30
+        })
+    );
+}
+
+#[derive(Debug, Clone, Default)]
+struct Mock {
+    reads: Vec<usize>,
+    writes: Vec<(usize, C)>,
+}
+
+impl Device for Mock {
+    fn read(&mut self, offset: usize) -> C {
+        self.reads.push(offset);
+        100 + offset as C
+    }
+
+    fn write(&mut self, offset: usize, value: C) {
+        self.writes.push((offset, value));
+    }
+}
+
+#[test]
+fn test_devices() {
+    let mut computer = Computer::new(vec![
+        1, 3001, 3002, 3003, 4, 3000, 109, 2990, 21201, 10, 5, 11, 99,
+    ]);
+    computer.attach(3000, 4, Mock::default());
+    let fresh = computer.clone();
+    let result = computer.run();
+    assert_eq!(result.state, State::Halted);
+    assert_eq!(result.outputs, vec![100]);
+    let mock = result.bus.get::<Mock>(3000).unwrap();
+    assert_eq!(mock.reads, vec![1, 2, 0, 0]);
+    assert_eq!(mock.writes, vec![(3, 203), (1, 105)]);
+    assert!(fresh.bus.get::<Mock>(3000).unwrap().reads.is_empty());
+    assert!(result.bus.get::<Timer>(3000).is_none());
+
+    let mut computer = Computer::new(vec![
+        4, 100, 4, 100, 1101, 104, 0, 200, 1101, 105, 0, 200, 1001, 200, 0, 50, 4, 50, 1101, 7, 0,
+        303, 99,
+    ]);
+    computer.attach(100, 1, Timer::default());
+    computer.attach(200, 1, Console::default());
+    computer.attach(300, 4, Framebuffer::new(2, 2));
+    computer
+        .bus
+        .get_mut::<Console>(200)
+        .unwrap()
+        .input
+        .push_back(b'Z');
+    let result = computer.run();
+    assert_eq!(result.outputs, vec![0, 1, 90]);
+    assert_eq!(result.bus.get::<Timer>(100).unwrap().ticks, 8);
+    assert_eq!(result.bus.get::<Console>(200).unwrap().output, "hi");
+    assert_eq!(result.bus.get::<Framebuffer>(300).unwrap().get(1, 1), 7);
+
+    let mut a = Entropy::new(3);
+    let mut b = Entropy::new(3);
+    let values: Vec<C> = (0..10).map(|_| a.read(0)).collect();
+    assert_eq!(values, (0..10).map(|_| b.read(0)).collect::<Vec<_>>());
+    assert!(values.iter().all(|&v| v >= 0));
+}
+
+#[test]
+#[should_panic(expected = "overlaps another device")]
+fn test_device_overlap() {
+    let mut computer = Computer::new(vec![99]);
+    computer.attach(100, 4, Timer::default());
+    computer.attach(103, 1, Timer::default());
+}
+
+/// A 3x3 arcade screen followed by a score, as a cabinet would output it.
+const SCREEN: [C; 30] = [
+    0, 0, 1, 1, 0, 1, 2, 0, 1, 0, 1, 1, 1, 1, 2, 2, 1, 1, 0, 2, 1, 1, 2, 4, 2, 2, 3, -1, 0, 42,
+];
+
+#[test]
+fn test_display() {
+    let mut display = Display::new();
+    display.feed(SCREEN[..4].iter().cloned());
+    assert_eq!(display.get(1, 0), None);
+    display.feed(SCREEN[4..].iter().cloned());
+    assert_eq!(display.render(&Palette::arcade()), "###\n#+#\n#o=\n");
+    assert_eq!(display.score, Some(42));
+    assert_eq!(display.count(1), 6);
+    assert_eq!(display.find(4).collect::<Vec<_>>(), vec![(1, 2)]);
+    assert_eq!(display.bounds(), Some(((0, 0), (2, 2))));
+
+    display.feed(vec![1, 1, 0, -1, 0, 7]);
+    assert_eq!(display.count(2), 0);
+    assert_eq!(display.score, Some(7));
+
+    let mut sparse = Display::new();
+    sparse.feed(vec![-2, -1, 1, 1, 0, 1]);
+    assert_eq!(sparse.render(&Palette::arcade()), "#???\n???#\n");
+    assert_eq!(Display::new().render(&Palette::arcade()), "");
+
+    let mut computer = Computer::new(vec![104, 1, 104, 2, 104, 3, 99]).run();
+    let mut display = Display::new();
+    display.update(&mut computer);
+    assert!(computer.outputs.is_empty());
+    assert_eq!(display.get(1, 2), Some(3));
+}
+
+#[test]
+fn test_display_ppm() {
+    let mut display = Display::new();
+    display.feed(vec![5, 5, 4]);
+    let palette = Palette::new().with(4, 'o', [250, 220, 40]);
+    let mut image = Vec::new();
+    display.write_ppm(&mut image, &palette, 2).unwrap();
+    let mut expected = b"P6\n2 2\n255\n".to_vec();
+    for _ in 0..4 {
+        expected.extend_from_slice(&[250, 220, 40]);
+    }
+    assert_eq!(image, expected);
+
+    display.feed(vec![6, 5, 9]);
+    let mut image = Vec::new();
+    display.write_ppm(&mut image, &palette, 1).unwrap();
+    assert_eq!(&image[11..], &[250, 220, 40, 255, 0, 255]);
+}
+
+/// A brain that ignores the camera and replays (colour, turn) pairs.
+fn scripted_brain(moves: &[(C, C)]) -> Computer {
+    let mut program = vec![
+        109, 50, 3, 100, 204, 0, 204, 1, 109, 2, 1001, 101, -1, 101, 1005, 101, 2, 99,
+    ];
+    program.resize(102, 0);
+    for (i, &(color, turn)) in moves.iter().enumerate() {
+        program[50 + 2 * i] = color;
+        program[51 + 2 * i] = turn;
+    }
+    program[101] = moves.len() as C;
+    Computer::new(program)
+}
+
+#[test]
+fn test_robot() {
+    let moves = [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)];
+    let mut robot = Robot::new(scripted_brain(&moves));
+    assert_eq!(robot.run(), RobotState::Halted);
+    assert_eq!(robot.painted.len(), 6);
+    assert_eq!(robot.steps, 7);
+    assert_eq!(robot.position, (0, -1));
+    assert_eq!(robot.facing, Direction::Left);
+    assert_eq!(robot.render(), "..#\n..#\n##.\n");
+
+    let mut robot = Robot::new(scripted_brain(&moves)).with_step_limit(3);
+    assert_eq!(robot.run(), RobotState::StepLimit);
+    assert_eq!(robot.painted.len(), 3);
+
+    let mut robot = Robot::new(scripted_brain(&[(1, 0), (1, 2)]));
+    assert_eq!(robot.run(), RobotState::BadTurn(2));
+    assert_eq!(robot.painted.len(), 1);
+    assert_eq!(robot.steps, 1);
+}
+
+#[test]
+fn test_robot_camera() {
+    // paints whatever it sees and turns right
+    let mirror = Computer::new(vec![3, 100, 4, 100, 104, 1, 1105, 1, 0]);
+    let mut robot = Robot::new(mirror.clone())
+        .with_start_color(1)
+        .with_step_limit(8);
+    assert_eq!(robot.run(), RobotState::StepLimit);
+    assert_eq!(robot.painted.len(), 4);
+    assert_eq!(robot.position, (0, 0));
+    assert_eq!(robot.render(), "#\n");
+
+    let mut robot = Robot::new(mirror).with_step_limit(4);
+    robot.run();
+    assert_eq!(robot.render(), "");
+
+    let mut robot = Robot::new(Computer::new(vec![3, 100, 104, 1, 3, 100, 99]));
+    assert_eq!(robot.run(), RobotState::Stalled);
+}
+
+/// Needs two quarters in cell 103. Each of five frames draws the paddle on
+/// row 1 and the ball at (3, 0), reports the paddle's x as the score and
+/// moves the paddle by the joystick tilt.
+fn cabinet() -> Memory {
+    let mut program = vec![
+        1008, 103, 2, 104, 1006, 104, 56, 4, 100, 104, 1, 104, 3, 104, 3, 104, 0, 104, 4, 104, -1,
+        104, 0, 4, 100, 3, 101, 4, 100, 104, 1, 104, 0, 1, 100, 101, 100, 1001, 102, -1, 102, 1005,
+        102, 7, 4, 100, 104, 1, 104, 3, 104, -1, 104, 0, 4, 100, 99,
+    ];
+    program.resize(105, 0);
+    program[102] = 5;
+    program[103] = 1;
+    program
+}
+
+#[test]
+fn test_arcade() {
+    let mut arcade = Arcade::new(cabinet()).with_patches(&[(103, 2)]).recording();
+    let game = arcade.play(&mut BallTracker);
+    assert_eq!(game.state, State::Halted);
+    assert_eq!(game.score, Some(3));
+    assert_eq!(game.frames, 6);
+    assert_eq!(arcade.screen.render(&Palette::arcade()), "???o\n   =\n");
+
+    let mut replay = Vec::new();
+    arcade.write_replay(&mut replay).unwrap();
+    let replay = String::from_utf8(replay).unwrap();
+    assert!(replay.starts_with("frame 1 score 0\n???o\n=???\n\nframe 2 score 1\n"));
+    assert_eq!(replay.matches("frame").count(), 6);
+
+    let game = Arcade::new(cabinet()).play(&mut BallTracker);
+    assert_eq!(game.score, None);
+    assert_eq!(game.frames, 1);
+
+    let mut tilts = Vec::new();
+    let mut arcade = Arcade::new(cabinet())
+        .with_patches(&[(103, 2)])
+        .with_frame_limit(3);
+    let game = arcade.play(&mut |screen: &Display| {
+        tilts.push(screen.score);
+        -1
+    });
+    assert_eq!(game.state, State::AwaitingInput);
+    assert_eq!(game.score, Some(-2));
+    assert_eq!(tilts, vec![Some(0), Some(-1)]);
+}
+
+const MAZE: [&str; 5] = ["########", "#..#..2#", "#.##.#.#", "#......#", "########"];
+
+/// A droid in `MAZE`, starting in the top-left open cell. It keeps the grid
+/// at 300 and points the relative base at the cell it is asked to move to.
+fn maze_droid() -> Computer {
+    let mut program = vec![
+        3, 202, 1001, 200, 0, 203, 1001, 201, 0, 204, 1008, 202, 1, 205, 1006, 205, 21, 1001, 204,
+        -1, 204, 1008, 202, 2, 205, 1006, 205, 32, 1001, 204, 1, 204, 1008, 202, 3, 205, 1006, 205,
+        43, 1001, 203, -1, 203, 1008, 202, 4, 205, 1006, 205, 54, 1001, 203, 1, 203, 1002, 204, 8,
+        206, 1, 206, 203, 206, 1001, 206, 300, 206, 1002, 207, -1, 208, 1, 206, 208, 208, 9, 208,
+        1001, 206, 0, 207, 1201, 0, 0, 209, 4, 209, 1006, 209, 0, 1001, 203, 0, 200, 1001, 204, 0,
+        201, 1105, 1, 0,
+    ];
+    program.resize(300, 0);
+    program[200] = 1;
+    program[201] = 1;
+    for row in MAZE.iter() {
+        program.extend(row.chars().map(|c| match c {
+            '#' => 0,
+            '.' => 1,
+            _ => 2,
+        }));
+    }
+    Computer::new(program)
+}
+
+#[test]
+fn test_maze() {
+    let maze = Maze::explore(&maze_droid(), 0).unwrap();
+    assert_eq!(maze.target, Some((5, 0)));
+    assert_eq!(
+        maze.render(),
+        " ## ### \n#D.#..O#\n#.##.#.#\n#......#\n ###### \n"
+    );
+    assert_eq!(maze.order.len(), 14);
+
+    let path = maze.path_to_target().unwrap();
+    assert_eq!(path.len(), 9);
+    let end = path.iter().fold((0, 0), |position, step| {
+        let next = step.apply(position);
+        assert!(maze.is_open(next));
+        next
+    });
+    assert_eq!(end, (5, 0));
+
+    let distances = maze.distances((5, 0));
+    assert_eq!(distances.len(), 14);
+    assert_eq!(distances.values().max(), Some(&10));
+    assert_eq!(distances[&(1, 0)], 10);
+    assert_eq!(maze.shortest_path((0, 0), (9, 9)), None);
+}
+
+#[test]
+fn test_maze_errors() {
+    let confused = Computer::new(vec![3, 100, 104, 5, 99]);
+    assert!(matches!(
+        Maze::explore(&confused, 0),
+        Err(MazeError::BadStatus { status: 5, .. })
+    ));
+    let silent = Computer::new(vec![3, 100, 99]);
+    assert!(matches!(
+        Maze::explore(&silent, 0),
+        Err(MazeError::Silent {
+            event: Event::Halted,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_maze_seeds() {
+    let droid = maze_droid();
+    let first = Maze::explore(&droid, 1).unwrap();
+    assert_eq!(first, Maze::explore(&droid, 1).unwrap());
+    let others: Vec<Maze> = (2..6)
+        .map(|seed| Maze::explore(&droid, seed).unwrap())
+        .collect();
+    assert!(others.iter().all(|maze| maze.tiles == first.tiles));
+    assert!(others.iter().any(|maze| maze.order != first.order));
+}
+
+#[test]
+fn test_rng() {
+    // seeds that would once have left xorshift stuck on zero
+    for &seed in [0x9e37_79b9_7f4a_7c15, 0x61c8_8646_80b5_83eb, 0].iter() {
+        let mut rng = Rng::new(seed);
+        let draws: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+        assert!(draws.iter().all(|&x| x != 0), "{:#x}", seed);
+        assert!(draws.windows(2).all(|w| w[0] != w[1]), "{:#x}", seed);
+    }
+    let mut rng = Rng::new(7);
+    assert!((0..100).all(|_| (-3..4).contains(&rng.range(-3, 4))));
+    let mut items: Vec<usize> = (0..10).collect();
+    rng.shuffle(&mut items);
+    items.sort();
+    assert_eq!(items, (0..10).collect::<Vec<_>>());
+}
+
+/// Outputs 1 after reading 3, 1, 2 and halts on the first wrong digit.
+const LOCK: [C; 30] = [
+    3, 100, 1008, 100, 3, 101, 1006, 101, 29, 3, 100, 1008, 100, 1, 101, 1006, 101, 29, 3, 100,
+    1008, 100, 2, 101, 1006, 101, 29, 104, 1, 99,
+];
+
+#[test]
+fn test_search_strategies() {
+    let lock = Computer::new(LOCK.to_vec());
+    let digits = [vec![1], vec![2], vec![3]];
+    let opened = |computer: &Computer| computer.outputs == [1];
+    for &strategy in [
+        Strategy::BreadthFirst,
+        Strategy::DepthFirst,
+        Strategy::IterativeDeepening,
+        Strategy::BestFirst,
+    ]
+    .iter()
+    {
+        let found = Search::new(&digits, opened)
+            .with_strategy(strategy)
+            .with_heuristic(|computer| -(computer.counter as i64))
+            .run(&lock)
+            .found()
+            .unwrap();
+        assert_eq!(found.moves, vec![2, 0, 1], "{:?}", strategy);
+        assert_eq!(found.inputs, vec![3, 1, 2]);
+        assert_eq!(found.computer.state, State::Halted);
+    }
+
+    let bfs = Search::new(&digits, opened).run(&lock).found().unwrap();
+    assert_eq!(bfs.explored, 9);
+
+    match Search::new(&digits, opened).with_max_states(4).run(&lock) {
+        SearchOutcome::StateLimit { explored } => assert_eq!(explored, 4),
+        other => panic!("{:?}", other),
+    }
+    match Search::new(&digits, |_: &Computer| false).run(&lock) {
+        SearchOutcome::Exhausted { explored } => assert_eq!(explored, 9),
+        other => panic!("{:?}", other),
+    }
+    let shallow = Search::new(&digits, opened)
+        .with_strategy(Strategy::IterativeDeepening)
+        .with_max_depth(2)
+        .run(&lock);
+    assert!(shallow.found().is_none());
+}
+
+#[test]
+fn test_search_limits() {
+    // reads forever, remembering nothing but the last value
+    let sink = Computer::new(vec![3, 100, 1105, 1, 0]);
+    let same = [vec![5], vec![5]];
+    match Search::new(&same, |_: &Computer| false)
+        .with_machine_dedup()
+        .run(&sink)
+    {
+        SearchOutcome::Exhausted { explored } => assert_eq!(explored, 4),
+        other => panic!("{:?}", other),
+    }
+    // echoes each value then forgets it, so every state matches the start
+    // bar what it has printed
+    let mut echo = vec![3, 20, 4, 20, 1101, 0, 0, 20, 1105, 1, 0];
+    echo.resize(21, 0);
+    let echo = Computer::new(echo);
+    let echoes = [vec![1], vec![2]];
+    match Search::new(&echoes, |_: &Computer| false)
+        .with_machine_dedup()
+        .run(&echo)
+    {
+        SearchOutcome::Exhausted { explored } => assert_eq!(explored, 2),
+        other => panic!("{:?}", other),
+    }
+    let by_output = Search::new(&echoes, |_: &Computer| false)
+        .with_dedup(Computer::fingerprint)
+        .with_max_states(50)
+        .run(&echo);
+    assert!(matches!(by_output, SearchOutcome::StateLimit { .. }));
+    let unbounded = Search::new(&same, |_: &Computer| false)
+        .with_max_states(50)
+        .run(&sink);
+    assert!(matches!(
+        unbounded,
+        SearchOutcome::StateLimit { explored: 50 }
+    ));
+
+    // spins without ever asking for input again
+    let spinner = Computer::new(vec![3, 100, 1105, 1, 2]);
+    let outcome = Search::new(&[vec![0]], |_: &Computer| true)
+        .with_max_steps(100)
+        .run(&spinner);
+    assert_eq!(outcome.found().unwrap().moves, Vec::<usize>::new());
+    let outcome = Search::new(&[vec![0]], |c: &Computer| c.counter > 1)
+        .with_max_steps(100)
+        .run(&spinner);
+    assert!(matches!(outcome, SearchOutcome::Exhausted { explored: 0 }));
+}
+
+#[test]
+fn test_fingerprint() {
+    let program = parse("1,9,10,3,2,3,11,0,99,30,40,50");
+    let computer = Computer::with_level(program.clone(), Level::Day02).run();
+    assert_eq!(computer.memory_hash, fingerprint::memory(computer.memory()));
+
+    // padding with zeroes changes nothing
+    let short = Computer::with_level(program.clone(), Level::Day02);
+    let padded = Computer::new(program.clone());
+    assert_ne!(short.memory.len(), padded.memory.len());
+    assert_eq!(short.fingerprint(), padded.fingerprint());
+
+    // bookkeeping only counts under `Identity::Full`
+    let mut a = Computer::new(program.clone());
+    let mut b = a.clone();
+    b.counter = 7;
+    assert_eq!(a, b);
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    a.identity = Identity::Full;
+    b.identity = Identity::Full;
+    assert_ne!(a, b);
+    assert_ne!(a.fingerprint(), b.fingerprint());
+
+    let mut patched = Computer::new(program);
+    patched.set(1, 12);
+    assert_ne!(patched, padded);
+    patched.set(1, 9);
+    assert_eq!(patched, padded);
+
+    let machines: std::collections::HashSet<_> =
+        vec![padded.clone(), patched, padded].into_iter().collect();
+    assert_eq!(machines.len(), 1);
+}
+
+#[test]
+fn test_paged_memory() {
+    let program: Memory = (0..600).collect();
+    let mut memory = PagedMemory::from(program.clone());
+    assert_eq!(memory.len(), 600);
+    assert_eq!(memory, program);
+    assert_eq!(memory.get(599), Some(599));
+    assert_eq!(memory.get(600), None);
+    assert_eq!(&memory.window(250, 4)[..], &[250, 251, 252, 253][..]);
+    assert_eq!(&memory.window(598, 4)[..], &[598, 599][..]);
+
+    // clones share every page until one is written
+    let copy = memory.clone();
+    assert_eq!(memory.shared_pages(&copy), 3);
+    assert_eq!(memory.set(300, -1), 300);
+    assert_eq!(memory.shared_pages(&copy), 2);
+    assert_eq!(copy[300], 300);
+    assert_ne!(memory, copy);
+    memory.set(300, 300);
+    assert_eq!(memory, copy);
+
+    memory.pad(2048);
+    assert_eq!(memory.len(), 2048);
+    assert_eq!(memory[2047], 0);
+    assert_eq!(memory.to_vec()[..600], program[..]);
+
+    // a computer and its clones only copy the pages they write to
+    let machine = Computer::new(program);
+    let mut clone = machine.clone();
+    clone.set(1000, 7);
+    assert_eq!(
+        machine.memory.shared_pages(&clone.memory),
+        2048 / PAGE_SIZE - 1
+    );
+    assert_eq!(machine.memory[1000], 0);
+}
+
+#[test]
+fn test_run_in_place() {
+    // the day 9 quine, which prints itself
+    let quine = parse("109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99");
+    let mut computer = Computer::new(quine.clone());
+    assert_eq!(computer.run_to_event(), Event::Output(109));
+    assert_eq!(computer.run_to_event(), Event::Output(1));
+    assert_eq!(computer.run_steps(3), Event::StepLimit);
+    assert_eq!(computer.counter, 10);
+    // decoding reuses buffers, but `last` still holds the last instruction
+    assert_eq!(computer.last.raw, vec![1006, 101, 0]);
+    assert_eq!(computer.run_steps(1), Event::StepLimit);
+    assert_eq!(computer.last.raw, vec![109, 1]);
+    assert_eq!(computer.run_to_halt(), Event::Halted);
+    assert_eq!(computer.outputs, quine);
+    assert_eq!(computer.run_to_event(), Event::Halted);
+
+    // the cloning wrappers leave the original alone
+    let start = Computer::new(quine.clone());
+    let copy = start.run();
+    assert_eq!(start.counter, 0);
+    assert_eq!(copy.outputs, computer.outputs);
+    assert_eq!(start.run_until(Some(8)).counter, 9);
+
+    // waiting machines carry on once given input
+    let mut echo = Computer::new(ECHO.to_vec());
+    assert_eq!(echo.run_to_halt(), Event::AwaitingInput);
+    echo.queue_inputs(vec![104, 105]);
+    assert_eq!(echo.run_to_event(), Event::Output(104));
+    assert_eq!(echo.run_to_event(), Event::Output(105));
+    assert_eq!(echo.run_to_event(), Event::AwaitingInput);
+}
+
+#[test]
+fn test_outputs_iter() {
+    // doubles each input
+    let doubler = Computer::new(vec![3, 9, 1002, 9, 2, 9, 4, 9, 1105, 1, 0]);
+    let mut computer = doubler.clone();
+    let values: Vec<C> = computer
+        .outputs_iter(vec![1, 2, 3])
+        .map_while(Event::output)
+        .collect();
+    assert_eq!(values, vec![2, 4, 6]);
+    assert!(computer.outputs.is_empty());
+
+    let mut computer = doubler;
+    let mut outputs = computer.outputs_iter(Some(5));
+    assert_eq!(outputs.next(), Some(Event::Output(10)));
+    assert_eq!(outputs.next(), Some(Event::AwaitingInput));
+    assert_eq!(outputs.next(), None);
+    outputs.feed(Some(7));
+    assert_eq!(outputs.next(), Some(Event::Output(14)));
+    assert_eq!(outputs.computer().counter, 8);
+
+    // a halt just ends it
+    let mut quine = Computer::new(parse(
+        "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99",
+    ));
+    assert_eq!(quine.outputs_iter(None).count(), 16);
+    assert_eq!(quine.state, State::Halted);
+
+    let mut trap = Computer::with_level(vec![104, 1, 109, 1, 99], Level::Day05);
+    let events: Vec<Event> = trap.outputs_iter(None).collect();
+    assert_eq!(
+        events,
+        vec![Event::Output(1), Event::Trapped(Violation::Opcode(9))]
+    );
+}
+
+#[test]
+fn test_load() {
+    let expected = vec![1, -2, 3, 99];
+    for text in [
+        "1,-2,3,99",
+        "1,-2,3,99,\n\n",
+        "  1, -2,\n3 ,99\n",
+        "1\n-2\n3\n99\n",
+        "# comment\n1,-2 # two\n3,99",
+        "[1, -2, 3, 99]",
+        "[\n  1,\n  -2,\n  3,\n  99\n]\n",
+    ]
+    .iter()
+    {
+        assert_eq!(parse_program(text).unwrap(), expected, "{:?}", text);
+    }
+    assert_eq!(
+        read_program(io::Cursor::new("4,5\n6")).unwrap(),
+        vec![4, 5, 6]
+    );
+
+    let error = parse_program("1,2\n3, x4,99").unwrap_err();
+    assert_eq!(error.to_string(), "2:4: bad value \"x4\"");
+    // columns count characters, not bytes
+    assert_eq!(
+        parse_program("10,\u{e9}200, 3,-x").unwrap_err().to_string(),
+        "1:4: bad value \"\u{e9}200\""
+    );
+    assert_eq!(
+        parse_program("10,200, 3,-x").unwrap_err().to_string(),
+        "1:11: bad value \"-x\""
+    );
+    assert!(matches!(
+        parse_program("1,99999999999999999999"),
+        Err(LoadError::BadValue {
+            line: 1,
+            column: 3,
+            ..
+        })
+    ));
+    assert_eq!(
+        parse_program("[1,\n2").unwrap_err().to_string(),
+        "2:2: missing ']'"
+    );
+    assert_eq!(
+        parse_program("[1] 2").unwrap_err().to_string(),
+        "1:5: values after the closing ']'"
+    );
+    assert_eq!(
+        parse_program("1, [2]").unwrap_err().to_string(),
+        "1:4: unexpected '['"
+    );
+    assert!(matches!(
+        parse_program(" # nothing\n"),
+        Err(LoadError::Empty)
+    ));
+    assert!(matches!(
+        load_program("/nonexistent/program.txt"),
+        Err(LoadError::Io(_))
+    ));
+}
+
+#[test]
+fn test_image() {
+    let image = Image::new(vec![1, -1, 63, -64, 64, C::MAX, C::MIN, 99])
+        .with_level(Level::Day05)
+        .with_entry(7)
+        .with_name("edges")
+        .with_source_hash(0xfeed);
+    let bytes = image.to_bytes();
+    assert_eq!(&bytes[..4], &MAGIC[..]);
+    assert_eq!(Image::from_bytes(&bytes).unwrap(), image);
+    assert_eq!(image.computer().eip, 7);
+    assert_eq!(image.computer().profile, Profile::day05());
+
+    // header, entry and count, then words in -64..64 take a byte and 99 two
+    let small = Image::new(vec![1, 0, 0, 0, 99]);
+    assert_eq!(small.to_bytes().len(), 4 + 3 + 1 + 1 + 4 + 2);
+    assert_eq!(small.to_text(), "1,0,0,0,99");
+    let mut written = Vec::new();
+    small.write(&mut written).unwrap();
+    assert_eq!(Image::read(&written[..]).unwrap(), small);
+
+    let text = "1,9,10,3,\n2,3,11,0,99,30,40,50\n";
+    let converted = Image::from_text(text).unwrap();
+    assert_eq!(converted.source_hash, Some(source_hash(text)));
+    assert_eq!(
+        parse_program(&converted.to_text()).unwrap(),
+        converted.memory
+    );
+
+    // every truncation is an error rather than a panic
+    for end in 0..bytes.len() {
+        assert!(Image::from_bytes(&bytes[..end]).is_err(), "{}", end);
+    }
+    let corrupt = |at: usize, byte: u8| {
+        let mut bytes = bytes.clone();
+        bytes[at] = byte;
+        Image::from_bytes(&bytes).unwrap_err().to_string()
+    };
+    assert_eq!(corrupt(0, b'X'), "Not an Intcode image");
+    assert_eq!(corrupt(4, 2), "Unsupported image version 2");
+    assert_eq!(corrupt(5, 3), "Unknown level 3");
+    assert_eq!(corrupt(6, 0x80), "Unknown flags 0x80");
+    assert_eq!(corrupt(7, 9), "Entry point 9 is past the end of 8 words");
+    assert_eq!(corrupt(9, 0xff), "Name at byte 9 isn't UTF-8");
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert_eq!(
+        Image::from_bytes(&longer).unwrap_err().to_string(),
+        format!("Unexpected data at byte {}", bytes.len())
+    );
+    let mut overlong = small.to_bytes();
+    overlong.truncate(8);
+    overlong.extend_from_slice(&[0xff; 10]);
+    overlong.push(0);
+    assert!(matches!(
+        Image::from_bytes(&overlong),
+        Err(ImageError::Overlong(8))
+    ));
+}
+
+/// Keeps every event's name and fields, and counts spans.
+#[derive(Clone, Default)]
+struct Collector {
+    events: Arc<std::sync::Mutex<Vec<(String, Fields)>>>,
+    spans: Arc<std::sync::atomic::AtomicU64>,
+}
+
+#[derive(Default)]
+struct Fields(std::collections::BTreeMap<String, String>);
+
+impl tracing::field::Visit for Fields {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl tracing::Subscriber for Collector {
+    fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        let id = self.spans.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        tracing::span::Id::from_u64(id + 1)
+    }
+
+    fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let name = event.metadata().name().to_string();
+        self.events.lock().unwrap().push((name, fields));
+    }
+
+    fn enter(&self, _: &tracing::span::Id) {}
+
+    fn exit(&self, _: &tracing::span::Id) {}
+}
+
+#[test]
+fn test_tracing() {
+    let collector = Collector::default();
+    let mut computer = Computer::new(parse("1,9,10,3,2,3,11,0,99,30,40,50"));
+    tracing::subscriber::with_default(collector.clone(), || computer.run_to_halt());
+    assert_eq!(collector.spans.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    let events = collector.events.lock().unwrap();
+    let field = |event: &(String, Fields), name: &str| event.1 .0.get(name).cloned();
+    let steps: Vec<_> = events.iter().filter(|e| e.0 == "step").collect();
+    let opcodes: Vec<_> = steps.iter().map(|e| field(e, "opcode").unwrap()).collect();
+    assert_eq!(opcodes, vec!["1", "2", "99"]);
+    assert_eq!(field(steps[1], "eip").as_deref(), Some("4"));
+    assert_eq!(field(steps[1], "counter").as_deref(), Some("1"));
+
+    // writes to address 3, found without parsing any text
+    let writes: Vec<_> = events
+        .iter()
+        .filter(|e| e.0 == "write" && field(e, "address").as_deref() == Some("3"))
+        .map(|e| (field(e, "value").unwrap(), field(e, "mode").unwrap()))
+        .collect();
+    assert_eq!(writes, vec![("70".to_string(), "Position".to_string())]);
+    assert_eq!(events.iter().filter(|e| e.0 == "read").count(), 4);
+}
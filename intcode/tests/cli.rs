@@ -0,0 +1,108 @@
+//! Runs the `intcode` binary on small programs fed through stdin.
+
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+
+struct Run {
+    code: i32,
+    stdout: String,
+    stderr: String,
+}
+
+fn intcode(program: &str, args: &[&str]) -> Run {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_intcode"))
+        .arg("-")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Can't start intcode");
+    // usage errors exit without reading the program
+    match child.stdin.take().unwrap().write_all(program.as_bytes()) {
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => {}
+        result => result.unwrap(),
+    }
+    let output = child.wait_with_output().unwrap();
+    Run {
+        code: output.status.code().expect("Killed by a signal"),
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+    }
+}
+
+#[test]
+fn test_halted() {
+    let run = intcode("3,0,4,0,99", &["-i", "42"]);
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "42\n");
+
+    let run = intcode("1,0,0,0,99", &["-p", "1=4", "-m", "0"]);
+    assert_eq!(run.code, 0, "{}", run.stderr);
+    assert_eq!(run.stdout, "[0] = 100\n");
+}
+
+#[test]
+fn test_errors() {
+    // output is outside day 2
+    let run = intcode("104,7,99", &["-l", "2"]);
+    assert_eq!(run.code, 1);
+    assert!(run.stderr.contains("trapped at eip=0"), "{}", run.stderr);
+
+    let run = intcode("1,x,99", &[]);
+    assert_eq!(run.code, 1);
+    assert!(
+        run.stderr.contains("1:3: bad value \"x\""),
+        "{}",
+        run.stderr
+    );
+
+    let run = intcode("42,0,99", &[]);
+    assert_eq!(run.code, 1);
+    assert!(run.stderr.contains("Bad instruction 42"), "{}", run.stderr);
+}
+
+#[test]
+fn test_usage() {
+    for args in [&["-l", "7"][..], &["-i", "x"], &["--steps"], &["--bogus"]].iter() {
+        let run = intcode("99", args);
+        assert_eq!(run.code, 2, "{:?}", args);
+        assert!(run.stderr.contains("usage: intcode"), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_blocked_and_step_limit() {
+    let run = intcode("3,0,3,0,99", &["-i", "1"]);
+    assert_eq!(run.code, 3);
+    assert!(run.stderr.contains("waiting for input at eip=2"));
+
+    // jumps to itself forever
+    let run = intcode("1105,1,0", &["-s", "10"]);
+    assert_eq!(run.code, 4);
+    assert!(run.stderr.contains("stopped after 10 steps"));
+}
+
+#[test]
+fn test_trace_and_profile() {
+    let run = intcode("104,7,104,8,99", &["--trace", "--profile"]);
+    assert_eq!(run.code, 0);
+    assert_eq!(run.stdout, "7\n8\n");
+    let lines: Vec<&str> = run.stderr.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "       1 0000 out 7",
+            "       2 0002 out 8",
+            "       3 0004 halt",
+            "3 instructions",
+            "         2 out",
+            "         1 halt",
+        ]
+    );
+
+    // the step limit still applies while tracing
+    let run = intcode("1105,1,0", &["-t", "-s", "3"]);
+    assert_eq!(run.code, 4);
+    assert_eq!(run.stderr.lines().count(), 4);
+}
@@ -0,0 +1,9 @@
+//! Interpreters that the fuzzer and the conformance suite check against
+//! each other: a plain reference interpreter and the shared `intcode` crate
+//! at each level.
+
+mod reference;
+mod targets;
+
+pub use reference::Reference;
+pub use targets::{all_targets, Outcome, Run, Shared, Target};
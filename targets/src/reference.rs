@@ -0,0 +1,133 @@
+use crate::targets::guarded;
+use crate::{Outcome, Run, Target};
+use intcode::Level;
+
+/// A deliberately plain interpreter written straight from the puzzle text,
+/// used as the oracle the shared crate is checked against. Memory grows on
+/// demand instead of being padded up front.
+pub struct Reference;
+
+struct Machine {
+    memory: Vec<i64>,
+    ip: usize,
+    base: i64,
+}
+
+impl Machine {
+    fn mode(&self, param: usize) -> i64 {
+        self.memory[self.ip] / 10_i64.pow(param as u32 + 1) % 10
+    }
+
+    fn load(&self, addr: usize) -> i64 {
+        self.memory.get(addr).cloned().unwrap_or(0)
+    }
+
+    fn addr(&self, param: usize) -> usize {
+        let raw = self.memory[self.ip + param];
+        match self.mode(param) {
+            0 => raw as usize,
+            2 => (self.base + raw) as usize,
+            mode => panic!("can't address with mode {}", mode),
+        }
+    }
+
+    fn arg(&self, param: usize) -> i64 {
+        match self.mode(param) {
+            1 => self.memory[self.ip + param],
+            _ => self.load(self.addr(param)),
+        }
+    }
+
+    fn store(&mut self, param: usize, value: i64) {
+        let addr = self.addr(param);
+        if addr >= self.memory.len() {
+            self.memory.resize(addr + 1, 0);
+        }
+        self.memory[addr] = value;
+    }
+}
+
+impl Target for Reference {
+    fn name(&self) -> &'static str {
+        "reference"
+    }
+
+    fn level(&self) -> Level {
+        Level::Day09
+    }
+
+    fn execute(&self, program: &[i64], inputs: &[i64], limit: usize) -> Run {
+        guarded(|| {
+            let mut m = Machine {
+                memory: program.to_vec(),
+                ip: 0,
+                base: 0,
+            };
+            let mut inputs = inputs.iter();
+            let mut outputs = Vec::new();
+            let mut outcome = Outcome::StepLimit;
+            for _ in 0..=limit {
+                match m.memory[m.ip] % 100 {
+                    1 => {
+                        m.store(3, m.arg(1) + m.arg(2));
+                        m.ip += 4;
+                    }
+                    2 => {
+                        m.store(3, m.arg(1) * m.arg(2));
+                        m.ip += 4;
+                    }
+                    3 => match inputs.next() {
+                        Some(&value) => {
+                            m.store(1, value);
+                            m.ip += 2;
+                        }
+                        None => {
+                            outcome = Outcome::AwaitingInput;
+                            break;
+                        }
+                    },
+                    4 => {
+                        outputs.push(m.arg(1));
+                        m.ip += 2;
+                    }
+                    5 => {
+                        m.ip = if m.arg(1) != 0 {
+                            m.arg(2) as usize
+                        } else {
+                            m.ip + 3
+                        }
+                    }
+                    6 => {
+                        m.ip = if m.arg(1) == 0 {
+                            m.arg(2) as usize
+                        } else {
+                            m.ip + 3
+                        }
+                    }
+                    7 => {
+                        m.store(3, (m.arg(1) < m.arg(2)) as i64);
+                        m.ip += 4;
+                    }
+                    8 => {
+                        m.store(3, (m.arg(1) == m.arg(2)) as i64);
+                        m.ip += 4;
+                    }
+                    9 => {
+                        m.base += m.arg(1);
+                        m.ip += 2;
+                    }
+                    99 => {
+                        outcome = Outcome::Halted;
+                        break;
+                    }
+                    code => panic!("bad opcode {}", code),
+                }
+            }
+            Run {
+                outcome,
+                memory: m.memory,
+                outputs,
+            }
+        })
+    }
+}
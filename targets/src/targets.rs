@@ -0,0 +1,104 @@
+use crate::Reference;
+use intcode::{Computer, Level, State};
+use std::panic::{self, AssertUnwindSafe};
+use Outcome::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Halted,
+    AwaitingInput,
+    StepLimit,
+    Crashed(String),
+}
+
+/// What an interpreter left behind after running a case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub outcome: Outcome,
+    pub memory: Vec<i64>,
+    pub outputs: Vec<i64>,
+}
+
+impl Run {
+    /// Two crashes agree regardless of message or partial state, since the
+    /// interpreters panic in different places.
+    pub fn agrees(&self, other: &Run) -> bool {
+        match (&self.outcome, &other.outcome) {
+            (Crashed(_), Crashed(_)) => true,
+            _ => self == other,
+        }
+    }
+}
+
+/// An interpreter under test. Inputs are given in the order they are read.
+pub trait Target {
+    fn name(&self) -> &'static str;
+    fn level(&self) -> Level;
+    fn execute(&self, program: &[i64], inputs: &[i64], limit: usize) -> Run;
+}
+
+pub(crate) fn guarded<F: FnOnce() -> Run>(f: F) -> Run {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(run) => run,
+        Err(cause) => {
+            let message = match cause.downcast_ref::<String>() {
+                Some(s) => s.clone(),
+                None => match cause.downcast_ref::<&str>() {
+                    Some(s) => s.to_string(),
+                    None => String::from("panic"),
+                },
+            };
+            Run {
+                outcome: Crashed(message),
+                memory: Vec::new(),
+                outputs: Vec::new(),
+            }
+        }
+    }
+}
+
+/// The shared `intcode` crate, restricted to one level.
+pub struct Shared(pub Level);
+
+impl Target for Shared {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            Level::Day02 => "intcode@day02",
+            Level::Day05 => "intcode@day05",
+            Level::Day09 => "intcode@day09",
+        }
+    }
+
+    fn level(&self) -> Level {
+        self.0
+    }
+
+    fn execute(&self, program: &[i64], inputs: &[i64], limit: usize) -> Run {
+        guarded(|| {
+            let mut computer = Computer::with_level(program.to_vec(), self.0);
+            computer.inputs = inputs.iter().rev().cloned().collect();
+            let result = computer.run_until(Some(limit));
+            Run {
+                outcome: match result.state {
+                    State::Halted => Halted,
+                    State::AwaitingInput => AwaitingInput,
+                    State::Running => StepLimit,
+                    State::Trapped(violation) => Crashed(violation.to_string()),
+                    State::Fault(error) => Crashed(error.to_string()),
+                },
+                memory: result.memory().to_vec(),
+                outputs: result.outputs,
+            }
+        })
+    }
+}
+
+/// Every interpreter to check, oracle first.
+pub fn all_targets() -> Vec<Box<dyn Target>> {
+    vec![
+        Box::new(Reference),
+        Box::new(Shared(Level::Day02)),
+        Box::new(Shared(Level::Day05)),
+        Box::new(Shared(Level::Day09)),
+    ]
+}